@@ -0,0 +1,382 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{self, Write};
+use core::ops::{Add, Mul, Sub};
+
+use crate::{BitIndex, FibIndex, FibNum};
+
+/// Operands shorter than this many limbs are multiplied with the schoolbook method.
+const KARATSUBA_THRESHOLD: usize = 32;
+
+/// An arbitrary-precision unsigned integer.
+///
+/// Stored as little-endian 64-bit limbs. It implements [`FibNum`] and [`FibIndex`], so it
+/// can be used with [`fib`](crate::fib) and [`fibbonacci`](crate::fibbonacci) to compute
+/// exact fibbonacci numbers of any size.
+///
+/// # Examples
+/// ```rust
+/// use quickfib::BigUint;
+///
+/// let x = quickfib::fib::<BigUint>(200u32);
+/// assert_eq!(x.to_string(), "280571172992510140037611932413038677189525");
+/// assert_eq!(format!("{:x}", x), "338864a5c1caeb07d0ef067cb83df17e395");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    fn from_limbs(mut limbs: Vec<u64>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigUint { limbs }
+    }
+
+    /// The number of significant bits, `0` for zero.
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() as u64 * 64 - u64::from(top.leading_zeros()),
+            None => 0,
+        }
+    }
+}
+
+macro_rules! impl_from_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for BigUint {
+                fn from(value: $t) -> Self {
+                    BigUint::from(u128::from(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_primitive!(u8, u16, u32, u64);
+
+impl From<u128> for BigUint {
+    fn from(value: u128) -> Self {
+        BigUint::from_limbs(vec![value as u64, (value >> 64) as u64])
+    }
+}
+
+impl From<usize> for BigUint {
+    fn from(value: usize) -> Self {
+        BigUint::from(value as u128)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn add_slices(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut out = Vec::with_capacity(long.len() + 1);
+    let mut carry = false;
+    for (i, &x) in long.iter().enumerate() {
+        let (s, c1) = x.overflowing_add(short.get(i).copied().unwrap_or(0));
+        let (s, c2) = s.overflowing_add(u64::from(carry));
+        out.push(s);
+        carry = c1 || c2;
+    }
+    if carry {
+        out.push(1);
+    }
+    out
+}
+
+/// `acc[offset..] += x`. The sum must fit in `acc`.
+fn add_assign_at(acc: &mut [u64], x: &[u64], offset: usize) {
+    let mut carry = false;
+    for (i, &d) in x.iter().enumerate() {
+        let (s, c1) = acc[offset + i].overflowing_add(d);
+        let (s, c2) = s.overflowing_add(u64::from(carry));
+        acc[offset + i] = s;
+        carry = c1 || c2;
+    }
+    let mut i = offset + x.len();
+    while carry && i < acc.len() {
+        let (s, c) = acc[i].overflowing_add(1);
+        acc[i] = s;
+        carry = c;
+        i += 1;
+    }
+}
+
+/// `a -= b`. The caller guarantees `a >= b`.
+fn sub_assign(a: &mut [u64], b: &[u64]) {
+    let mut borrow = false;
+    for (i, x) in a.iter_mut().enumerate() {
+        if i >= b.len() && !borrow {
+            break;
+        }
+        let (d, b1) = x.overflowing_sub(b.get(i).copied().unwrap_or(0));
+        let (d, b2) = d.overflowing_sub(u64::from(borrow));
+        *x = d;
+        borrow = b1 || b2;
+    }
+}
+
+fn mul_schoolbook(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let mut out = vec![0; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u128;
+        for (j, &y) in b.iter().enumerate() {
+            let t = u128::from(x) * u128::from(y) + u128::from(out[i + j]) + carry;
+            out[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        out[i + b.len()] = carry as u64;
+    }
+    out
+}
+
+fn mul_slices(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    if short.is_empty() {
+        return Vec::new();
+    }
+    if short.len() < KARATSUBA_THRESHOLD {
+        return mul_schoolbook(long, short);
+    }
+
+    let mut out = vec![0; long.len() + short.len()];
+    if short.len() * 2 <= long.len() {
+        for (k, chunk) in long.chunks(short.len()).enumerate() {
+            add_assign_at(&mut out, &mul_slices(chunk, short), k * short.len());
+        }
+        return out;
+    }
+
+    let m = long.len() / 2;
+    let (l0, l1) = long.split_at(m);
+    let (s0, s1) = short.split_at(m);
+    let z0 = mul_slices(l0, s0);
+    let z2 = mul_slices(l1, s1);
+    let mut z1 = mul_slices(&add_slices(l0, l1), &add_slices(s0, s1));
+    sub_assign(&mut z1, &z0);
+    sub_assign(&mut z1, &z2);
+    while z1.last() == Some(&0) {
+        z1.pop();
+    }
+
+    add_assign_at(&mut out, &z0, 0);
+    add_assign_at(&mut out, &z1, m);
+    add_assign_at(&mut out, &z2, 2 * m);
+    out
+}
+
+/// Divide `limbs` in place by `d`, returning the remainder.
+#[inline]
+fn div_rem_u32(limbs: &mut [u64], d: u32) -> u32 {
+    let d = u64::from(d);
+    let mut rem = 0u64;
+    for limb in limbs.iter_mut().rev() {
+        let hi = (rem << 32) | (*limb >> 32);
+        let lo = ((hi % d) << 32) | (*limb & 0xffff_ffff);
+        *limb = ((hi / d) << 32) | (lo / d);
+        rem = lo % d;
+    }
+    rem as u32
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, rhs: &BigUint) -> BigUint {
+        BigUint::from_limbs(add_slices(&self.limbs, &rhs.limbs))
+    }
+}
+
+impl Sub for &BigUint {
+    type Output = BigUint;
+
+    fn sub(self, rhs: &BigUint) -> BigUint {
+        assert!(*self >= *rhs, "attempt to subtract with overflow");
+        let mut limbs = self.limbs.clone();
+        sub_assign(&mut limbs, &rhs.limbs);
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, rhs: &BigUint) -> BigUint {
+        BigUint::from_limbs(mul_slices(&self.limbs, &rhs.limbs))
+    }
+}
+
+macro_rules! forward_by_value {
+    ($($imp:ident, $method:ident);*) => {
+        $(
+            impl $imp for BigUint {
+                type Output = BigUint;
+
+                fn $method(self, rhs: BigUint) -> BigUint {
+                    (&self).$method(&rhs)
+                }
+            }
+        )*
+    };
+}
+
+forward_by_value!(Add, add; Sub, sub; Mul, mul);
+
+impl FibNum for BigUint {
+    fn zero() -> Self {
+        BigUint::default()
+    }
+
+    fn one() -> Self {
+        BigUint { limbs: vec![1] }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+}
+
+impl BitIndex for BigUint {
+    fn bit_len(&self) -> u64 {
+        self.bits()
+    }
+
+    fn bit(&self, i: u64) -> bool {
+        let limb = (i / 64) as usize;
+        limb < self.limbs.len() && (self.limbs[limb] >> (i % 64)) & 1 == 1
+    }
+}
+
+impl FibIndex for BigUint {
+    fn is_even(&self) -> bool {
+        self.limbs.first().is_none_or(|l| l & 1 == 0)
+    }
+
+    fn halve(&self) -> Self {
+        let mut limbs = self.limbs.clone();
+        let mut carry = 0;
+        for limb in limbs.iter_mut().rev() {
+            let next = *limb << 63;
+            *limb = (*limb >> 1) | carry;
+            carry = next;
+        }
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const CHUNK: u32 = 1_000_000_000;
+
+        let mut limbs = self.limbs.clone();
+        let mut chunks = Vec::new();
+        while !limbs.is_empty() {
+            chunks.push(div_rem_u32(&mut limbs, CHUNK));
+            while limbs.last() == Some(&0) {
+                limbs.pop();
+            }
+        }
+
+        let mut s = String::with_capacity(chunks.len() * 9);
+        match chunks.split_last() {
+            Some((top, rest)) => {
+                write!(s, "{}", top)?;
+                for chunk in rest.iter().rev() {
+                    write!(s, "{:09}", chunk)?;
+                }
+            }
+            None => s.push('0'),
+        }
+        f.pad_integral(true, "", &s)
+    }
+}
+
+macro_rules! impl_hex {
+    ($($imp:ident => $first:literal, $rest:literal);*) => {
+        $(
+            impl fmt::$imp for BigUint {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    let mut s = String::with_capacity(self.limbs.len() * 16);
+                    match self.limbs.split_last() {
+                        Some((top, rest)) => {
+                            write!(s, $first, top)?;
+                            for limb in rest.iter().rev() {
+                                write!(s, $rest, limb)?;
+                            }
+                        }
+                        None => s.push('0'),
+                    }
+                    f.pad_integral(true, "0x", &s)
+                }
+            }
+        )*
+    };
+}
+
+impl_hex!(LowerHex => "{:x}", "{:016x}"; UpperHex => "{:X}", "{:016X}");
+
+#[cfg(test)]
+mod tests {
+
+    use super::BigUint;
+    use crate::{fib, fibbonacci, FibIndex, FibNum};
+    use alloc::format;
+    use alloc::string::ToString;
+
+    fn additive_fib(n: u32) -> BigUint {
+        let (mut a, mut b) = (BigUint::zero(), BigUint::one());
+        for _ in 0..n {
+            let c = &a + &b;
+            a = b;
+            b = c;
+        }
+        a
+    }
+
+    #[test]
+    fn calc_1000() {
+        assert_eq!(
+            fib::<BigUint>(1000u32).to_string(),
+            "43466557686937456435688527675040625802564660517371780402481729089536555417949051890403879840079255169295922593080322634775209689623239873322471161642996440906533187938298969649928516003704476137795166849228875"
+        );
+    }
+
+    #[test]
+    fn calc_matches_additive() {
+        // Large enough to go through the Karatsuba path.
+        for n in [0, 1, 2, 93, 94, 187, 5000, 20000] {
+            assert_eq!(fib::<BigUint>(n), additive_fib(n));
+        }
+        assert_eq!(fibbonacci(BigUint::from(300u32)), additive_fib(300));
+    }
+
+    #[test]
+    fn formatting() {
+        let x = BigUint::from(u128::MAX);
+        assert_eq!(x.to_string(), u128::MAX.to_string());
+        assert_eq!(format!("{:x}", x), format!("{:x}", u128::MAX));
+        assert_eq!(format!("{:#X}", x), format!("{:#X}", u128::MAX));
+        assert_eq!(format!("{:>5}", BigUint::zero()), "    0");
+        assert_eq!(x.bits(), 128);
+        assert_eq!(x.halve(), BigUint::from(u128::MAX >> 1));
+    }
+}
@@ -0,0 +1,213 @@
+use crate::modular::mul_wide;
+use crate::{FibError, FibModulus};
+
+/// Montgomery reduction with `R = 2^64`, for odd moduli.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Montgomery {
+    m: u64,
+    /// `m^-1 mod 2^64`.
+    m_inv: u64,
+    /// `R^2 mod m`.
+    r2: u64,
+}
+
+impl Montgomery {
+    fn new(m: u64) -> Self {
+        // Each Newton step doubles the number of correct low bits, starting from 3.
+        let mut m_inv = m;
+        for _ in 0..5 {
+            m_inv = m_inv.wrapping_mul(2u64.wrapping_sub(m.wrapping_mul(m_inv)));
+        }
+        let m_wide = u128::from(m);
+        let r2 = ((u128::MAX % m_wide + 1) % m_wide) as u64;
+        Montgomery { m, m_inv, r2 }
+    }
+
+    /// `t * R^-1 mod m`, for `t < m * 2^64`.
+    #[inline]
+    fn redc(&self, t: u128) -> u64 {
+        let q = (t as u64).wrapping_mul(self.m_inv);
+        let qm_hi = ((u128::from(q) * u128::from(self.m)) >> 64) as u64;
+        let t_hi = (t >> 64) as u64;
+        t_hi.sub_mod(qm_hi, self.m)
+    }
+}
+
+/// Barrett reduction with a precomputed `floor((2^128 - 1) / m)`, for any modulus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Barrett {
+    m: u64,
+    mu: u128,
+    /// `1 mod m`, which is `0` when `m` is `1`.
+    one: u64,
+}
+
+impl Barrett {
+    fn new(m: u64) -> Self {
+        Barrett {
+            m,
+            mu: u128::MAX / u128::from(m),
+            one: 1 % m,
+        }
+    }
+
+    /// `x mod m`, for `x < m^2`.
+    #[inline]
+    fn reduce(&self, x: u128) -> u64 {
+        let m = u128::from(self.m);
+        let q = mul_wide(x, self.mu).0;
+        let mut r = x - q * m;
+        while r >= m {
+            r -= m;
+        }
+        r as u64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Engine {
+    Montgomery(Montgomery),
+    Barrett(Barrett),
+}
+
+/// A modulus with precomputed reduction constants, for answering many
+/// fibbonacci queries modulo the same number.
+///
+/// Odd moduli use Montgomery multiplication and even moduli use Barrett reduction,
+/// so no query performs a hardware division.
+///
+/// # Examples
+/// ```rust
+/// use quickfib::FibModContext;
+///
+/// let ctx = FibModContext::new(1_000_000_007).unwrap();
+/// assert_eq!(ctx.fib(1_000_000_000_000_000_000u64), 209783453);
+/// assert_eq!(ctx.fib(10u8), 55);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FibModContext {
+    engine: Engine,
+}
+
+impl FibModContext {
+    /// Precompute the reduction constants for `m`, choosing Montgomery reduction when
+    /// `m` is odd and Barrett reduction otherwise.
+    ///
+    /// # Errors
+    /// Returns [`FibError::ModulusZero`] if `m` is zero.
+    pub fn new(m: u64) -> Result<Self, FibError> {
+        if m % 2 == 1 && m > 1 {
+            Ok(FibModContext {
+                engine: Engine::Montgomery(Montgomery::new(m)),
+            })
+        } else {
+            Self::barrett(m)
+        }
+    }
+
+    /// Precompute Barrett reduction constants for `m`, whatever its parity.
+    ///
+    /// # Errors
+    /// Returns [`FibError::ModulusZero`] if `m` is zero.
+    pub fn barrett(m: u64) -> Result<Self, FibError> {
+        if m == 0 {
+            return Err(FibError::ModulusZero);
+        }
+        Ok(FibModContext {
+            engine: Engine::Barrett(Barrett::new(m)),
+        })
+    }
+
+    /// The modulus of the context.
+    pub fn modulus(&self) -> u64 {
+        match self.engine {
+            Engine::Montgomery(mont) => mont.m,
+            Engine::Barrett(barrett) => barrett.m,
+        }
+    }
+
+    /// Calculate the n-th fibbonacci number modulo the context's modulus.
+    pub fn fib(&self, n: impl Into<u128>) -> u64 {
+        let n = n.into();
+        match self.engine {
+            Engine::Montgomery(mont) => {
+                let one = mont.redc(u128::from(mont.r2));
+                let (a, _) = fib_pair_with(n, mont.m, one, |a, b| {
+                    mont.redc(u128::from(a) * u128::from(b))
+                });
+                mont.redc(u128::from(a))
+            }
+            Engine::Barrett(barrett) => {
+                fib_pair_with(n, barrett.m, barrett.one, |a, b| {
+                    barrett.reduce(u128::from(a) * u128::from(b))
+                })
+                .0
+            }
+        }
+    }
+}
+
+/// Fast doubling over a representation where `one` stands for `1` and `mul`
+/// multiplies two represented values.
+#[inline]
+fn fib_pair_with(n: u128, m: u64, one: u64, mul: impl Fn(u64, u64) -> u64) -> (u64, u64) {
+    let mut a = 0;
+    let mut b = one;
+    for i in (0..u128::BITS - n.leading_zeros()).rev() {
+        let c = mul(a, b.add_mod(b, m).sub_mod(a, m));
+        let d = mul(a, a).add_mod(mul(b, b), m);
+        if (n >> i) & 1 == 1 {
+            (a, b) = (d, c.add_mod(d, m));
+        } else {
+            (a, b) = (c, d);
+        }
+    }
+    (a, b)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::FibModContext;
+    use crate::{fibbonacci_mod, FibError};
+
+    #[test]
+    fn matches_fibbonacci_mod() {
+        let moduli = [
+            1,
+            2,
+            3,
+            10,
+            1_000_000_007,
+            998_244_353,
+            1 << 40,
+            u64::MAX,
+            u64::MAX - 1,
+            18446744073709551557,
+        ];
+        for m in moduli {
+            let ctx = FibModContext::new(m).unwrap();
+            let barrett = FibModContext::barrett(m).unwrap();
+            assert_eq!(ctx.modulus(), m);
+            for n in [
+                0u128,
+                1,
+                2,
+                3,
+                90,
+                1000,
+                123_456_789,
+                u64::MAX as u128,
+                u128::MAX,
+            ] {
+                assert_eq!(ctx.fib(n), fibbonacci_mod(n, m));
+                assert_eq!(barrett.fib(n), fibbonacci_mod(n, m));
+            }
+        }
+    }
+
+    #[test]
+    fn zero_modulus() {
+        assert_eq!(FibModContext::new(0), Err(FibError::ModulusZero));
+    }
+}
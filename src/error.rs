@@ -0,0 +1,38 @@
+use core::fmt;
+
+/// The error type returned by the fallible fibbonacci functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FibError {
+    /// The result does not fit in the output type.
+    Overflow,
+    /// The index is negative, which the function does not support.
+    ///
+    /// No function in the crate returns this any more, since negative indices follow
+    /// the negafibonacci rule; it is kept so that existing matches still compile.
+    NegativeIndex,
+    /// The index is not an integer.
+    NonIntegralIndex,
+    /// The index string is not a valid decimal integer.
+    InvalidDigit,
+    /// The modulus is zero.
+    ModulusZero,
+    /// Memory for the result could not be allocated.
+    AllocationFailure,
+}
+
+impl fmt::Display for FibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FibError::Overflow => "fibbonacci number does not fit in the output type",
+            FibError::NegativeIndex => "fibbonacci index is negative",
+            FibError::NonIntegralIndex => "fibbonacci index is not an integer",
+            FibError::InvalidDigit => "invalid digit found in fibbonacci index",
+            FibError::ModulusZero => "modulus is zero",
+            FibError::AllocationFailure => "memory allocation failed",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FibError {}
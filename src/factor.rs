@@ -0,0 +1,217 @@
+use crate::FibModulus;
+
+/// A `u64` has at most 15 distinct prime factors.
+const MAX_FACTORS: usize = 15;
+
+const SMALL_PRIMES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// The prime factorisation of a `u64`, as `(prime, exponent)` pairs in ascending order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Factors {
+    factors: [(u64, u32); MAX_FACTORS],
+    len: usize,
+}
+
+impl Factors {
+    /// Factorise `n` with trial division, Miller-Rabin and Pollard-Brent rho.
+    pub(crate) fn of(mut n: u64) -> Self {
+        let mut factors = Factors {
+            factors: [(0, 0); MAX_FACTORS],
+            len: 0,
+        };
+        for p in SMALL_PRIMES {
+            while n.is_multiple_of(p) {
+                factors.push(p);
+                n /= p;
+            }
+        }
+        factors.factor_into(n);
+        factors.factors[..factors.len].sort_unstable();
+        factors
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u64, u32)> + '_ {
+        self.factors[..self.len].iter().copied()
+    }
+
+    pub(crate) fn primes(&self) -> impl Iterator<Item = u64> + '_ {
+        self.iter().map(|(p, _)| p)
+    }
+
+    pub(crate) fn into_primes(self) -> impl Iterator<Item = u64> {
+        self.factors.into_iter().take(self.len).map(|(p, _)| p)
+    }
+
+    fn push(&mut self, p: u64) {
+        match self.factors[..self.len].iter_mut().find(|(q, _)| *q == p) {
+            Some((_, e)) => *e += 1,
+            None => {
+                self.factors[self.len] = (p, 1);
+                self.len += 1;
+            }
+        }
+    }
+
+    fn factor_into(&mut self, n: u64) {
+        if n == 1 {
+            return;
+        }
+        if is_prime(n) {
+            self.push(n);
+            return;
+        }
+        let d = pollard_brent(n);
+        self.factor_into(d);
+        self.factor_into(n / d);
+    }
+}
+
+/// Binary GCD, which avoids hardware division.
+pub(crate) fn gcd(mut a: u64, mut b: u64) -> u64 {
+    if a == 0 || b == 0 {
+        return a | b;
+    }
+    let shift = (a | b).trailing_zeros();
+    a >>= a.trailing_zeros();
+    while b != 0 {
+        b >>= b.trailing_zeros();
+        if a > b {
+            (a, b) = (b, a);
+        }
+        b -= a;
+    }
+    a << shift
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn lcm(a: u64, b: u64) -> u64 {
+    a / gcd(a, b) * b
+}
+
+pub(crate) fn gcd_u128(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+pub(crate) fn lcm_u128(a: u128, b: u128) -> u128 {
+    a / gcd_u128(a, b) * b
+}
+
+fn pow_mod(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.mul_mod(base, m);
+        }
+        base = base.mul_mod(base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Deterministic Miller-Rabin; the first twelve primes as bases cover every `u64`.
+pub(crate) fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in SMALL_PRIMES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    'witness: for a in SMALL_PRIMES {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.mul_mod(x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// A non-trivial factor of the odd composite `n`.
+fn pollard_brent(n: u64) -> u64 {
+    const BATCH: u64 = 128;
+
+    for c in 1.. {
+        let f = |x: u64| x.mul_mod(x, n).add_mod(c, n);
+        let (mut x, mut y, mut ys) = (2, 2, 2);
+        let (mut r, mut q, mut g) = (1u64, 1u64, 1u64);
+        while g == 1 {
+            x = y;
+            for _ in 0..r {
+                y = f(y);
+            }
+            let mut k = 0;
+            while k < r && g == 1 {
+                ys = y;
+                for _ in 0..BATCH.min(r - k) {
+                    y = f(y);
+                    q = q.mul_mod(x.abs_diff(y), n);
+                }
+                g = gcd(q, n);
+                k += BATCH;
+            }
+            r *= 2;
+        }
+        if g == n {
+            loop {
+                ys = f(ys);
+                g = gcd(x.abs_diff(ys), n);
+                if g > 1 {
+                    break;
+                }
+            }
+        }
+        if g != n {
+            return g;
+        }
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{is_prime, Factors};
+
+    #[test]
+    fn primality() {
+        let primes = (0..200u64).filter(|&n| is_prime(n)).count();
+        assert_eq!(primes, 46);
+        assert!(is_prime(18446744073709551557));
+        assert!(!is_prime(3215031751));
+        assert!(!is_prime(u64::MAX));
+    }
+
+    #[test]
+    fn factorisation() {
+        let n = 600851475143;
+        let f = Factors::of(n);
+        assert!(f.iter().eq([(71, 1), (839, 1), (1471, 1), (6857, 1)]));
+        let f = Factors::of(4294967291 * 4294967279);
+        assert!(f.iter().eq([(4294967279, 1), (4294967291, 1)]));
+        let f = Factors::of(u64::MAX);
+        assert!(f.iter().eq([
+            (3, 1),
+            (5, 1),
+            (17, 1),
+            (257, 1),
+            (641, 1),
+            (65537, 1),
+            (6700417, 1)
+        ]));
+        assert_eq!(Factors::of(1).iter().count(), 0);
+        assert!(Factors::of(1 << 63).iter().eq([(2, 63)]));
+    }
+}
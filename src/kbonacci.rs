@@ -0,0 +1,128 @@
+use crate::kitamasa::power_of_x;
+use crate::{FibModulus, FibNum};
+
+/// `c[K-1]` of `x^n` modulo `x^K - x^(K-1) - ... - x - 1`, which is the K-bonacci term for
+/// the initial values `0, ..., 0, 1`. Arrays of length `K` keep it free of allocation.
+fn kitamasa<T: Clone, const K: usize>(
+    n: u128,
+    zero: T,
+    one: T,
+    add: impl Fn(T, T) -> T,
+    mul: impl Fn(T, T) -> T,
+) -> T {
+    assert!(K > 0, "kbonacci order is zero");
+    let coefficients: [T; K] = core::array::from_fn(|_| one.clone());
+    let mut c: [T; K] = core::array::from_fn(|i| if i == 0 { one.clone() } else { zero.clone() });
+    let mut scratch: [T; K] = core::array::from_fn(|_| zero.clone());
+    let c = power_of_x(n, &coefficients, &mut c, &mut scratch, zero, add, mul);
+    c[K - 1].clone()
+}
+
+/// Calculate the n-th K-bonacci number, where each term is the sum of the `K` before it.
+/// The function may panic if the type Out is not large enough to hold the result.
+///
+/// The sequence starts with `K - 1` zeros and a one, so `K = 2` gives the fibbonacci
+/// numbers, `K = 3` the tribonacci numbers `0, 0, 1, 1, 2, 4, 7, ...` and `K = 4` the
+/// tetranacci numbers. The n-th term is read off `x^n` modulo the characteristic
+/// polynomial (Kitamasa's method), which takes `O(K^2 log n)` operations.
+///
+/// # Panics
+/// Panics if `K` is zero.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::kbonacci::<3, u64>(10u8), 81);
+/// assert_eq!(quickfib::kbonacci::<4, u64>(10u8), 56);
+/// assert_eq!(quickfib::kbonacci::<2, u64>(20u8), quickfib::fib::<u64>(20u8));
+/// ```
+pub fn kbonacci<const K: usize, Out: FibNum>(n: impl Into<u64>) -> Out {
+    kitamasa::<Out, K>(
+        n.into().into(),
+        Out::zero(),
+        Out::one(),
+        |a, b| a + b,
+        |a, b| a * b,
+    )
+}
+
+/// Calculate the n-th K-bonacci number modulo `m`.
+/// Intermediate products are widened, so the function never overflows.
+///
+/// # Panics
+/// Panics if `K` or `m` is zero.
+///
+/// # Examples
+/// ```rust
+/// let x = quickfib::kbonacci_mod::<3, u64>(1_000_000_000_000_000_000u64, 1_000_000_007);
+/// assert_eq!(x, 913728402);
+/// ```
+pub fn kbonacci_mod<const K: usize, M: FibModulus>(n: impl Into<u128>, m: M) -> M {
+    assert!(
+        m != M::ZERO,
+        "attempt to calculate the remainder with a divisor of zero"
+    );
+    kitamasa::<M, K>(
+        n.into(),
+        M::ZERO,
+        M::ONE.reduce(m),
+        |a, b| a.add_mod(b, m),
+        |a, b| a.mul_mod(b, m),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{kbonacci, kbonacci_mod};
+    use crate::{fib, fibbonacci_mod};
+
+    fn naive<const K: usize>(n: usize) -> u128 {
+        let mut a = [0u128; K];
+        a[K - 1] = 1;
+        for _ in 0..n {
+            let next = a.iter().sum();
+            a.rotate_left(1);
+            a[K - 1] = next;
+        }
+        a[0]
+    }
+
+    #[test]
+    fn matches_naive() {
+        for n in 0..=60u8 {
+            assert_eq!(kbonacci::<3, u128>(n), naive::<3>(n.into()));
+            assert_eq!(kbonacci::<4, u128>(n), naive::<4>(n.into()));
+            assert_eq!(kbonacci::<7, u128>(n), naive::<7>(n.into()));
+            assert_eq!(kbonacci::<1, u128>(n), 1);
+            assert_eq!(kbonacci::<2, u128>(n), fib(n));
+        }
+        assert_eq!(kbonacci::<3, u64>(75u8), 12903063846126135669);
+        assert_eq!(kbonacci::<5, u64>(70u8), 12778498417777343135);
+    }
+
+    #[test]
+    fn modular() {
+        let m = 998_244_353u64;
+        for n in 0..=60u8 {
+            assert_eq!(
+                kbonacci_mod::<3, u64>(n, m),
+                (naive::<3>(n.into()) % u128::from(m)) as u64
+            );
+        }
+        for n in [0u128, 1, 1000, u128::MAX] {
+            assert_eq!(kbonacci_mod::<2, u64>(n, m), fibbonacci_mod(n, m));
+        }
+        assert_eq!(kbonacci_mod::<5, u64>(10u64.pow(18), m), 448425112);
+        assert_eq!(
+            kbonacci_mod::<3, u64>(u128::MAX, u64::MAX - 58),
+            1249996907308774881
+        );
+        assert_eq!(kbonacci_mod::<3, u8>(5u8, 1), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "kbonacci order is zero")]
+    fn order_zero() {
+        kbonacci::<0, u64>(5u8);
+    }
+}
@@ -0,0 +1,48 @@
+/// Reduce `x^n` modulo the characteristic polynomial `x^k - c[0] x^(k-1) - ... - c[k-1]`
+/// of a linear recurrence, with `add` and `mul` as the ring operations.
+///
+/// This is Kitamasa's method: square-and-multiply on polynomials of degree below `k`,
+/// with products reduced on the fly by Horner's rule, so only the two caller-provided
+/// buffers of length `k` are needed and nothing is allocated. `c` must hold the
+/// polynomial `1`, that is `one, zero, ..., zero`. Returns whichever buffer ends up
+/// holding the coefficients, lowest degree first.
+pub(crate) fn power_of_x<'a, T: Clone>(
+    n: u128,
+    coefficients: &[T],
+    mut c: &'a mut [T],
+    mut scratch: &'a mut [T],
+    zero: T,
+    add: impl Fn(T, T) -> T,
+    mul: impl Fn(T, T) -> T,
+) -> &'a [T] {
+    let k = coefficients.len();
+    debug_assert!(k > 0 && c.len() == k && scratch.len() == k);
+
+    // p = x * p, using x^k = c[0] x^(k-1) + ... + c[k-1].
+    let times_x = |p: &mut [T]| {
+        let top = p[k - 1].clone();
+        for i in (1..k).rev() {
+            p[i] = add(
+                p[i - 1].clone(),
+                mul(coefficients[k - 1 - i].clone(), top.clone()),
+            );
+        }
+        p[0] = mul(coefficients[k - 1].clone(), top);
+    };
+
+    for i in (0..u128::BITS - n.leading_zeros()).rev() {
+        // scratch = c * c, reduced with Horner's rule.
+        scratch.fill(zero.clone());
+        for j in (0..k).rev() {
+            times_x(scratch);
+            for (r, q) in scratch.iter_mut().zip(c.iter()) {
+                *r = add(r.clone(), mul(c[j].clone(), q.clone()));
+            }
+        }
+        core::mem::swap(&mut c, &mut scratch);
+        if (n >> i) & 1 == 1 {
+            times_x(c);
+        }
+    }
+    c
+}
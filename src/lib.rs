@@ -7,59 +7,313 @@
 //! F(2n) = F(n) * (2 * F(n+1) - F(n))
 //! F(2n+1) = F(n)^2 + F(n+1)^2
 //! ```
+//!
+//! ## Features
+//!
+//! - `alloc`: enables the functions returning a `Vec`, such as [`fibbonacci_range`], and
+//!   the arbitrary-precision [`BigUint`], the batch [`PisanoTable`] and the general
+//!   [`LinearRecurrence`].
+//! - `num`: implements [`FibNum`] and [`FibIndex`] for the `num-bigint` integer types.
+//! - `std` (default): implements `std::error::Error` for [`FibError`] and provides the
+//!   real-valued [`fib_real`]. Implies `alloc`.
+
+#![no_std]
 
-use core::ops::{Add, Div, Mul, Rem, Sub};
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+mod biguint;
+mod context;
+mod error;
+mod factor;
+mod kbonacci;
+mod kitamasa;
+mod modular;
+mod num;
+mod overflow;
+mod pisano;
+mod real;
+#[cfg(feature = "alloc")]
+mod recurrence;
+mod sequence;
+#[cfg(feature = "alloc")]
+mod table;
+mod uint;
+
+#[cfg(feature = "alloc")]
+pub use biguint::BigUint;
+pub use context::FibModContext;
+pub use error::FibError;
+pub use kbonacci::{kbonacci, kbonacci_mod};
+pub use modular::{
+    fibbonacci_mod, fibbonacci_mod_big, fibbonacci_mod_str, gibonacci_mod, try_fibbonacci_mod,
+    FibModulus,
+};
+pub use num::{BitIndex, FibIndex, FibNum};
+pub use overflow::{
+    checked_fibbonacci, checked_gibonacci, checked_lucas, overflowing_fibbonacci,
+    overflowing_lucas, saturating_fibbonacci, saturating_lucas, wrapping_fibbonacci,
+    wrapping_lucas, FibPrimitive,
+};
+pub use pisano::{entry_point, pisano_period};
+#[cfg(feature = "std")]
+pub use real::fib_real;
+pub use real::{fib_approx_f32, fib_approx_f64};
+#[cfg(feature = "alloc")]
+pub use recurrence::{berlekamp_massey, LinearRecurrence};
+pub use sequence::{jacobsthal, lucas_uv, lucas_uv_mod, pell, pell_lucas, try_lucas_uv_mod};
+#[cfg(feature = "alloc")]
+pub use sequence::{jacobsthal_range, pell_lucas_range, pell_range};
+#[cfg(feature = "alloc")]
+pub use table::PisanoTable;
+pub use uint::{Uint, U1024, U256, U512};
 
 /// Calculate the n-th fibbonacci number.
 /// The function may panic if the type T is not large enough to hold the result.
+/// See [`try_fibbonacci`] for a fallible alternative, and [`checked_fibbonacci`]
+/// and its siblings for well-defined overflow behaviour.
+///
+/// Negative indices follow the negafibonacci rule `F(-n) = (-1)^(n+1) F(n)`.
+///
+/// # Panics
+/// Panics if `n` is not a whole number, such as a float with a fractional part.
+/// See [`try_fibbonacci_index`] to get an error instead, and `fib_real` for the
+/// continuous extension to real arguments.
 ///
 /// # Examples
 /// ```rust
 /// let x = quickfib::fibbonacci(20);
 /// assert_eq!(x, 6765);
+/// assert_eq!(quickfib::fibbonacci(-8), -21);
+/// assert_eq!(quickfib::fibbonacci(10.0), 55.0);
 /// ```
+pub fn fibbonacci<T: FibIndex>(n: T) -> T {
+    assert!(n.is_integral(), "non-integral fibbonacci index");
+    __fibbonacci(n)
+}
 
-pub fn fibbonacci<T>(n: T) -> T
-where
-    T: From<u8>
-        + Add<Output = T>
-        + Sub<Output = T>
-        + Mul<Output = T>
-        + Div<Output = T>
-        + Rem<Output = T>
-        + PartialEq
-        + Copy,
-{
-    fn __fib<T>(n: T) -> (T, T)
-    where
-        T: From<u8>
-            + Add<Output = T>
-            + Sub<Output = T>
-            + Mul<Output = T>
-            + Div<Output = T>
-            + Rem<Output = T>
-            + PartialEq
-            + Copy,
-    {
-        if n == T::from(0) {
-            (T::from(0), T::from(1))
+/// Calculate the n-th fibbonacci number for any index type, returning an error instead of
+/// panicking on a non-integral index.
+/// The function may panic if the type T is not large enough to hold the result; see
+/// [`try_fibbonacci`] for primitive integers, which also reports overflow.
+///
+/// # Errors
+/// Returns [`FibError::NonIntegralIndex`] if `n` is not a whole number, such as a float
+/// with a fractional part, an infinity or NaN.
+///
+/// # Examples
+/// ```rust
+/// use quickfib::FibError;
+///
+/// assert_eq!(quickfib::try_fibbonacci_index(10.0), Ok(55.0));
+/// assert_eq!(quickfib::try_fibbonacci_index(2.5), Err(FibError::NonIntegralIndex));
+/// ```
+pub fn try_fibbonacci_index<T: FibIndex>(n: T) -> Result<T, FibError> {
+    if n.is_integral() {
+        Ok(__fibbonacci(n))
+    } else {
+        Err(FibError::NonIntegralIndex)
+    }
+}
+
+/// [`fibbonacci`] for an index already known to be whole.
+fn __fibbonacci<T: FibIndex>(n: T) -> T {
+    if n.is_zero() {
+        return T::zero();
+    }
+    // Halving rounds towards zero, so a negative index walks the same path as `|n|`.
+    let value = __last(__fib(&n.halve()), !n.is_even());
+    if n.is_negative() && n.is_even() {
+        T::zero() - value
+    } else {
+        value
+    }
+}
+
+/// Calculate the n-th Lucas number, `L(n) = F(n-1) + F(n+1)`.
+/// The function may panic if the type T is not large enough to hold the result.
+/// See [`checked_lucas`] and its siblings for well-defined overflow behaviour.
+///
+/// Negative indices follow `L(-n) = (-1)^n L(n)`.
+///
+/// # Panics
+/// Panics if `n` is not a whole number.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::lucas(10), 123);
+/// assert_eq!(quickfib::lucas(-5), -11);
+/// ```
+pub fn lucas<T: FibIndex>(n: T) -> T {
+    fib_lucas(n).1
+}
+
+/// Calculate the n-th fibbonacci and Lucas numbers together, as `(F(n), L(n))`.
+/// The function may panic if the type T is not large enough to hold either result.
+///
+/// Both come out of the same doubling steps, so this costs about as much as one call
+/// to [`fibbonacci`]. Negative indices follow `F(-n) = (-1)^(n+1) F(n)` and
+/// `L(-n) = (-1)^n L(n)`.
+///
+/// # Panics
+/// Panics if `n` is not a whole number.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::fib_lucas(10u32), (55, 123));
+/// assert_eq!(quickfib::fib_lucas(-3i32), (2, -4));
+/// ```
+pub fn fib_lucas<T: FibIndex>(n: T) -> (T, T) {
+    assert!(n.is_integral(), "non-integral fibbonacci index");
+    let (a, b) = __fib(&n);
+    // L(n) = 2F(n+1) - F(n), without the doubled intermediate.
+    let l = if b.is_finite() {
+        b.clone() + (b - a.clone())
+    } else {
+        b
+    };
+    if !n.is_negative() {
+        (a, l)
+    } else if n.is_even() {
+        (T::zero() - a, l)
+    } else {
+        (a, T::zero() - l)
+    }
+}
+
+/// Calculate the n-th term of the fibbonacci-like sequence with `G(0) = a`, `G(1) = b` and
+/// `G(n) = G(n-1) + G(n-2)`. The function may panic if the type T is not large enough to
+/// hold the result. See [`checked_gibonacci`] for a fallible alternative.
+///
+/// The term is `a F(n-1) + b F(n)`, from one fast-doubling pair. Negative indices extend
+/// the sequence backwards with `G(n-2) = G(n) - G(n-1)`.
+///
+/// # Panics
+/// Panics if `n` is not a whole number.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::gibonacci(3, 7, 10), 487);
+/// assert_eq!(quickfib::gibonacci(2, 1, 10), quickfib::lucas(10));
+/// assert_eq!(quickfib::gibonacci(3, 7, -5), 11);
+/// ```
+pub fn gibonacci<T: FibIndex>(a: T, b: T, n: T) -> T {
+    assert!(n.is_integral(), "non-integral fibbonacci index");
+    if n.is_zero() {
+        return a;
+    }
+    if n.is_negative() {
+        // With k = -n, F(n) = (-1)^(k+1) F(k) and F(n-1) = (-1)^k F(k+1).
+        let (x, y) = __fib(&n);
+        return if n.is_even() {
+            a * y - b * x
         } else {
-            let (a, b) = __fib(n / T::from(2));
-            let c = a * ((b * T::from(2)) - a);
-            let d = a * a + b * b;
-            if n % T::from(2) == T::from(0) {
-                (c, d)
-            } else {
-                (d, c + d)
-            }
-        }
+            b * x - a * y
+        };
+    }
+    let (x, y) = __fib(&(n - T::one()));
+    a * x + b * y
+}
+
+/// Calculate the n-th fibbonacci number with a machine-integer index.
+/// The function may panic if the type Out is not large enough to hold the result.
+///
+/// Unlike [`fibbonacci`], the index and the result have different types, so the
+/// result can be as wide as needed while the index stays a small integer. The bits
+/// of the index are scanned iteratively from the top, so `Out` only needs addition,
+/// subtraction and multiplication, and `F(n+1)` is never computed: the result is exact
+/// whenever `F(n)` itself fits in `Out`.
+///
+/// # Examples
+/// ```rust
+/// let x = quickfib::fib::<u128>(150u8);
+/// assert_eq!(x, 9969216677189303386214405760200);
+/// ```
+pub fn fib<Out: FibNum>(n: impl Into<u64>) -> Out {
+    let n = n.into();
+    if n == 0 {
+        return Out::zero();
+    }
+
+    let top = u64::BITS - 1 - n.leading_zeros();
+    let mut pair = (Out::one(), Out::one());
+    for i in (1..top).rev() {
+        pair = __double(pair, (n >> i) & 1 == 1);
+    }
+
+    if top == 0 {
+        pair.0
+    } else {
+        __last(pair, n & 1 == 1)
+    }
+}
+
+/// Calculate the n-th fibbonacci number with a signed machine-integer index.
+/// The function may panic if the type Out is not large enough to hold the result.
+///
+/// This is [`fib`] extended to negative indices with `F(-n) = (-1)^(n+1) F(n)`,
+/// so `Out` must be able to represent the negative values.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::fib_signed::<i64>(-6), -8);
+/// assert_eq!(quickfib::fib_signed::<i64>(-7), 13);
+/// ```
+pub fn fib_signed<Out: FibNum>(n: impl Into<i64>) -> Out {
+    let n = n.into();
+    let value = fib::<Out>(n.unsigned_abs());
+    if n < 0 && n % 2 == 0 {
+        Out::zero() - value
+    } else {
+        value
     }
+}
 
-    __fib(n).0
+/// The pair `(F(|n|), F(|n|+1))`.
+fn __fib<T: FibIndex>(n: &T) -> (T, T) {
+    if n.is_zero() {
+        (T::zero(), T::one())
+    } else {
+        __double(__fib(&n.halve()), !n.is_even())
+    }
+}
+
+/// Map the pair `(F(k), F(k+1))` to `(F(2k), F(2k+1))`, or to `(F(2k+1), F(2k+2))` if `odd`.
+fn __double<T: FibNum>((a, b): (T, T), odd: bool) -> (T, T) {
+    if !b.is_finite() {
+        // A float pair past its range stays at infinity.
+        return (b.clone(), b);
+    }
+    let c = a.clone() * (b.double() - a.clone());
+    let d = a.clone() * a + b.clone() * b;
+    if odd {
+        let e = c + d.clone();
+        (d, e)
+    } else {
+        (c, d)
+    }
+}
+
+/// Map the pair `(F(k), F(k+1))` to `F(2k)`, or to `F(2k+1)` if `odd`.
+fn __last<T: FibNum>((a, b): (T, T), odd: bool) -> T {
+    if !b.is_finite() {
+        return b;
+    }
+    if odd {
+        a.clone() * a + b.clone() * b
+    } else {
+        a.clone() * (b.double() - a)
+    }
 }
 
 /// Calculate the fibbonacci numbers for a range of numbers.
 /// The function may panic if the type U is not large enough to hold the result.
+/// See [`try_fibbonacci_range`] for a fallible alternative.
 ///
 /// # Examples
 /// ```rust
@@ -67,18 +321,11 @@ where
 /// let x = quickfib::fibbonacci_range(0..=9);
 /// assert_eq!(x, vec![0, 1, 1, 2, 3, 5, 8, 13, 21, 34]);
 /// ```
-
+#[cfg(feature = "alloc")]
 pub fn fibbonacci_range<T, U>(range: T) -> Vec<U>
 where
     T: IntoIterator<Item = U>,
-    U: From<u8>
-        + Add<Output = U>
-        + Sub<Output = U>
-        + Mul<Output = U>
-        + Div<Output = U>
-        + Rem<Output = U>
-        + PartialEq
-        + Copy,
+    U: FibIndex,
 {
     let mut result = Vec::new();
     for i in range {
@@ -87,10 +334,108 @@ where
     result
 }
 
+/// Calculate the Lucas numbers for a range of numbers.
+/// The function may panic if the type U is not large enough to hold the result.
+///
+/// # Examples
+/// ```rust
+/// let x = quickfib::lucas_range(0..=9);
+/// assert_eq!(x, vec![2, 1, 3, 4, 7, 11, 18, 29, 47, 76]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn lucas_range<T, U>(range: T) -> Vec<U>
+where
+    T: IntoIterator<Item = U>,
+    U: FibIndex,
+{
+    let mut result = Vec::new();
+    for i in range {
+        result.push(lucas(i));
+    }
+    result
+}
+
+/// Calculate the n-th fibbonacci number, returning an error instead of panicking.
+///
+/// # Errors
+/// Returns [`FibError::Overflow`] if the result does not fit in `T`.
+/// Negative indices follow `F(-n) = (-1)^(n+1) F(n)`.
+///
+/// # Examples
+/// ```rust
+/// use quickfib::FibError;
+///
+/// assert_eq!(quickfib::try_fibbonacci(20u32), Ok(6765));
+/// assert_eq!(quickfib::try_fibbonacci(100u64), Err(FibError::Overflow));
+/// assert_eq!(quickfib::try_fibbonacci(-4i32), Ok(-3));
+/// ```
+pub fn try_fibbonacci<T: FibPrimitive>(n: T) -> Result<T, FibError> {
+    checked_fibbonacci(n).ok_or(FibError::Overflow)
+}
+
+/// Calculate the n-th Lucas number, returning an error instead of panicking.
+///
+/// # Errors
+/// Returns [`FibError::Overflow`] if the result does not fit in `T`.
+///
+/// # Examples
+/// ```rust
+/// use quickfib::FibError;
+///
+/// assert_eq!(quickfib::try_lucas(11u8), Ok(199));
+/// assert_eq!(quickfib::try_lucas(12u8), Err(FibError::Overflow));
+/// ```
+pub fn try_lucas<T: FibPrimitive>(n: T) -> Result<T, FibError> {
+    checked_lucas(n).ok_or(FibError::Overflow)
+}
+
+/// Calculate the fibbonacci numbers for a range of numbers, returning an error instead of panicking.
+///
+/// # Errors
+/// Returns the first error produced by [`try_fibbonacci`], or
+/// [`FibError::AllocationFailure`] if the result vector cannot grow.
+///
+/// # Examples
+/// ```rust
+/// use quickfib::FibError;
+///
+/// let x = quickfib::try_fibbonacci_range(0u8..=13);
+/// assert_eq!(x.unwrap().last(), Some(&233));
+///
+/// let y = quickfib::try_fibbonacci_range(0u8..=14);
+/// assert_eq!(y, Err(FibError::Overflow));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn try_fibbonacci_range<T, U>(range: T) -> Result<Vec<U>, FibError>
+where
+    T: IntoIterator<Item = U>,
+    U: FibPrimitive,
+{
+    let range = range.into_iter();
+    let mut result = Vec::new();
+    result
+        .try_reserve(range.size_hint().0)
+        .map_err(|_| FibError::AllocationFailure)?;
+    for i in range {
+        result
+            .try_reserve(1)
+            .map_err(|_| FibError::AllocationFailure)?;
+        result.push(try_fibbonacci(i)?);
+    }
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
 
-    use super::{fibbonacci, fibbonacci_range};
+    use super::{
+        fib, fib_lucas, fib_signed, fibbonacci, gibonacci, lucas, try_fibbonacci,
+        try_fibbonacci_index, try_lucas, FibError,
+    };
+    #[cfg(feature = "alloc")]
+    use super::{fibbonacci_range, lucas_range, try_fibbonacci_range};
+    #[cfg(feature = "alloc")]
+    use alloc::vec;
 
     #[test]
     fn calc_1() {
@@ -117,8 +462,150 @@ mod tests {
     }
 
     #[test]
+    fn calc_wide() {
+        assert_eq!(fib::<u64>(20u8), 6765);
+        assert_eq!(fib::<u128>(150u32), 9969216677189303386214405760200);
+        assert_eq!(fib::<u128>(186u32), 332825110087067562321196029789634457848);
+        assert_eq!(fibbonacci(93u64), 12200160415121876738);
+        assert_eq!(fib::<f64>(10u16), 55.0);
+    }
+
+    #[test]
+    fn calc_matches_recursive() {
+        for n in 0..=93u64 {
+            assert_eq!(fib::<u64>(n), fibbonacci(n));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
     fn calc_range() {
         let result = fibbonacci_range(0..=9);
         assert_eq!(result, vec![0, 1, 1, 2, 3, 5, 8, 13, 21, 34]);
+        let result = fibbonacci_range(-5..=5);
+        assert_eq!(result, vec![5, -3, 2, -1, 1, 0, 1, 1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn try_calc() {
+        assert_eq!(try_fibbonacci(93u64), Ok(12200160415121876738));
+        assert_eq!(try_fibbonacci(94u64), Err(FibError::Overflow));
+        assert_eq!(try_fibbonacci(-1i64), Ok(1));
+        assert_eq!(try_fibbonacci(-92i64), Ok(-7540113804746346429));
+        assert_eq!(try_fibbonacci(-93i64), Err(FibError::Overflow));
+    }
+
+    #[test]
+    fn calc_negative() {
+        assert_eq!(fibbonacci(-1), 1);
+        assert_eq!(fibbonacci(-2), -1);
+        assert_eq!(fibbonacci(-7i32), 13);
+        assert_eq!(fibbonacci(-100i128), -354224848179261915075);
+        for n in -90..=90i64 {
+            assert_eq!(fib_signed::<i64>(n), fibbonacci(n));
+        }
+    }
+
+    #[test]
+    fn calc_lucas() {
+        assert_eq!(lucas(0), 2);
+        assert_eq!(lucas(1), 1);
+        assert_eq!(lucas(92u64), 16860207025497407047);
+        assert_eq!(lucas(-10), 123);
+        assert_eq!(lucas(-11), -199);
+        assert_eq!(lucas(20.0f64), 15127.0);
+        for n in -90..=90i64 {
+            let (f, l) = fib_lucas(n);
+            assert_eq!(f, fibbonacci(n));
+            if n > -90 && n < 90 {
+                assert_eq!(l, fibbonacci(n - 1) + fibbonacci(n + 1));
+            }
+        }
+        assert_eq!(try_lucas(-90i64), Ok(6440026026380244498));
+        assert_eq!(try_lucas(-91i64), Err(FibError::Overflow));
+    }
+
+    #[test]
+    fn calc_gibonacci() {
+        let expected = [11, -6, 5, -1, 4, 3, 7, 10, 17, 27, 44, 71, 115];
+        for (n, value) in (-5..=7).zip(expected) {
+            assert_eq!(gibonacci(3, 7, n), value, "n = {}", n);
+        }
+        assert_eq!(gibonacci(3u64, 7, 50), 111440109322);
+        assert_eq!(gibonacci(3u64, 7, 89), 15760119247131305116);
+        for n in -60..=60i64 {
+            assert_eq!(gibonacci(0, 1, n), fibbonacci(n));
+            assert_eq!(gibonacci(2, 1, n), lucas(n));
+        }
+        assert_eq!(gibonacci(1.0f64, 1.0, 10.0), 89.0);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn calc_lucas_range() {
+        let result = lucas_range(-3..=3);
+        assert_eq!(result, vec![-4, 3, -1, 2, 1, 3, 4]);
+    }
+
+    #[test]
+    fn calc_float() {
+        assert_eq!(fibbonacci(10.0f64), 55.0);
+        assert_eq!(fibbonacci(-10.0f64), -55.0);
+        assert_eq!(fibbonacci(78.0f64), 8944394323791464.0);
+        assert_eq!(fibbonacci(25.0f32), 75025.0);
+        assert_eq!(fibbonacci(2000.0f64), f64::INFINITY);
+        assert_eq!(fibbonacci(5000.0f64), f64::INFINITY);
+        assert_eq!(fibbonacci(-5000.0f64), f64::NEG_INFINITY);
+        assert_eq!(fibbonacci(5001.0f32), f32::INFINITY);
+        assert_eq!(fib::<f64>(5000u32), f64::INFINITY);
+        assert_eq!(fib::<f64>(1476u32), fibbonacci(1476.0));
+        assert_eq!(lucas(5000.0f64), f64::INFINITY);
+        assert_eq!(lucas(-5001.0f64), f64::NEG_INFINITY);
+        assert_eq!(fib_lucas(1475.0f64).1, f64::INFINITY);
+    }
+
+    #[test]
+    fn try_calc_index() {
+        assert_eq!(try_fibbonacci_index(-10.0f64), Ok(-55.0));
+        assert_eq!(try_fibbonacci_index(20u32), Ok(6765));
+        assert_eq!(
+            try_fibbonacci_index(2.5f64),
+            Err(FibError::NonIntegralIndex)
+        );
+        assert_eq!(
+            try_fibbonacci_index(f64::NAN),
+            Err(FibError::NonIntegralIndex)
+        );
+        assert_eq!(
+            try_fibbonacci_index(f32::INFINITY),
+            Err(FibError::NonIntegralIndex)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "non-integral fibbonacci index")]
+    fn calc_non_integral() {
+        fibbonacci(2.5f64);
+    }
+
+    #[test]
+    #[should_panic(expected = "non-integral fibbonacci index")]
+    fn calc_nan() {
+        fibbonacci(f64::NAN);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn try_calc_range() {
+        let result = try_fibbonacci_range(0i16..=9);
+        assert_eq!(result, Ok(vec![0, 1, 1, 2, 3, 5, 8, 13, 21, 34]));
+        assert_eq!(
+            try_fibbonacci_range([3i16, -2, -23]),
+            Ok(vec![2, -1, 28657])
+        );
+        assert_eq!(
+            try_fibbonacci_range([3i16, -2, 40]),
+            Err(FibError::Overflow)
+        );
     }
 }
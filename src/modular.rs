@@ -0,0 +1,366 @@
+use crate::{BitIndex, FibError};
+
+/// Unsigned integer types usable as a modulus by [`fibbonacci_mod`].
+///
+/// Products are computed in a wider type before reducing, so no intermediate value
+/// overflows: `u64` moduli go through `u128`, and `u128` moduli through a 256-bit product.
+/// The methods expect their operands to be already reduced modulo `m`.
+pub trait FibModulus: Copy + PartialEq + PartialOrd {
+    /// The value `0`.
+    const ZERO: Self;
+    /// The value `1`.
+    const ONE: Self;
+
+    /// `(self + rhs) mod m`.
+    fn add_mod(self, rhs: Self, m: Self) -> Self;
+    /// `(self - rhs) mod m`.
+    fn sub_mod(self, rhs: Self, m: Self) -> Self;
+    /// `(self * rhs) mod m`.
+    fn mul_mod(self, rhs: Self, m: Self) -> Self;
+    /// `self mod m`.
+    fn reduce(self, m: Self) -> Self;
+}
+
+macro_rules! impl_fib_modulus {
+    ($($t:ty => $wide:ty),*) => {
+        $(
+            impl FibModulus for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                #[inline]
+                fn add_mod(self, rhs: Self, m: Self) -> Self {
+                    let (s, overflow) = self.overflowing_add(rhs);
+                    if overflow || s >= m {
+                        s.wrapping_sub(m)
+                    } else {
+                        s
+                    }
+                }
+
+                #[inline]
+                fn sub_mod(self, rhs: Self, m: Self) -> Self {
+                    if self >= rhs {
+                        self - rhs
+                    } else {
+                        self.wrapping_sub(rhs).wrapping_add(m)
+                    }
+                }
+
+                #[inline]
+                fn mul_mod(self, rhs: Self, m: Self) -> Self {
+                    (self as $wide * rhs as $wide % m as $wide) as $t
+                }
+
+                #[inline]
+                fn reduce(self, m: Self) -> Self {
+                    self % m
+                }
+            }
+        )*
+    };
+}
+
+impl_fib_modulus!(u8 => u16, u16 => u32, u32 => u64, u64 => u128);
+
+#[cfg(target_pointer_width = "16")]
+impl_fib_modulus!(usize => u32);
+#[cfg(target_pointer_width = "32")]
+impl_fib_modulus!(usize => u64);
+#[cfg(target_pointer_width = "64")]
+impl_fib_modulus!(usize => u128);
+
+impl FibModulus for u128 {
+    const ZERO: Self = 0;
+    const ONE: Self = 1;
+
+    #[inline]
+    fn add_mod(self, rhs: Self, m: Self) -> Self {
+        let (s, overflow) = self.overflowing_add(rhs);
+        if overflow || s >= m {
+            s.wrapping_sub(m)
+        } else {
+            s
+        }
+    }
+
+    #[inline]
+    fn sub_mod(self, rhs: Self, m: Self) -> Self {
+        if self >= rhs {
+            self - rhs
+        } else {
+            self.wrapping_sub(rhs).wrapping_add(m)
+        }
+    }
+
+    fn mul_mod(self, rhs: Self, m: Self) -> Self {
+        if m <= u128::from(u64::MAX) {
+            return self * rhs % m;
+        }
+        let (hi, lo) = mul_wide(self, rhs);
+        let mut r = hi % m;
+        for i in (0..128).rev() {
+            r = r.add_mod(r, m);
+            if (lo >> i) & 1 == 1 {
+                r = r.add_mod(1, m);
+            }
+        }
+        r
+    }
+
+    #[inline]
+    fn reduce(self, m: Self) -> Self {
+        self % m
+    }
+}
+
+/// The full 256-bit product of `a` and `b`, as `(high, low)` halves.
+pub(crate) fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a1, a0) = (a >> 64, a & MASK);
+    let (b1, b0) = (b >> 64, b & MASK);
+    let p00 = a0 * b0;
+    let p01 = a0 * b1;
+    let p10 = a1 * b0;
+    let p11 = a1 * b1;
+    let mid = (p00 >> 64) + (p01 & MASK) + (p10 & MASK);
+    let lo = (p00 & MASK) | (mid << 64);
+    let hi = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+/// Map the pair `(F(k), F(k+1))` modulo `m` to the pair at `2k`, or at `2k+1` if `odd`.
+#[inline]
+pub(crate) fn double_mod<M: FibModulus>((a, b): (M, M), odd: bool, m: M) -> (M, M) {
+    let c = a.mul_mod(b.add_mod(b, m).sub_mod(a, m), m);
+    let d = a.mul_mod(a, m).add_mod(b.mul_mod(b, m), m);
+    if odd {
+        (d, c.add_mod(d, m))
+    } else {
+        (c, d)
+    }
+}
+
+/// Map the pairs at `j` and `k` modulo `m` to the pair at `j + k`.
+#[inline]
+fn add_mod_pairs<M: FibModulus>((a, b): (M, M), (c, d): (M, M), m: M) -> (M, M) {
+    let ac = a.mul_mod(c, m);
+    let e = a.mul_mod(d, m).add_mod(b.mul_mod(c, m), m).sub_mod(ac, m);
+    let f = b.mul_mod(d, m).add_mod(ac, m);
+    (e, f)
+}
+
+/// The pair `(F(n) mod m, F(n+1) mod m)`. The caller guarantees `m != 0`.
+pub(crate) fn fib_pair_mod<I: BitIndex + ?Sized, M: FibModulus>(n: &I, m: M) -> (M, M) {
+    let mut pair = (M::ZERO, M::ONE.reduce(m));
+    for i in (0..n.bit_len()).rev() {
+        pair = double_mod(pair, n.bit(i), m);
+    }
+    pair
+}
+
+/// Calculate the n-th fibbonacci number modulo `m`.
+/// Intermediate products are widened, so the function never overflows.
+///
+/// # Panics
+/// Panics if `m` is zero. See [`try_fibbonacci_mod`] for a fallible alternative.
+///
+/// # Examples
+/// ```rust
+/// let x = quickfib::fibbonacci_mod(1_000_000_000_000_000_000u64, 1_000_000_007u64);
+/// assert_eq!(x, 209783453);
+/// ```
+pub fn fibbonacci_mod<M: FibModulus>(n: impl Into<u128>, m: M) -> M {
+    assert!(
+        m != M::ZERO,
+        "attempt to calculate the remainder with a divisor of zero"
+    );
+    fib_pair_mod(&n.into(), m).0
+}
+
+/// Calculate the n-th term of the fibbonacci-like sequence starting from `a` and `b`,
+/// modulo `m`. Intermediate products are widened, so the function never overflows.
+///
+/// # Panics
+/// Panics if `m` is zero.
+///
+/// # Examples
+/// ```rust
+/// // The Lucas numbers start from 2 and 1.
+/// assert_eq!(quickfib::gibonacci_mod(2u64, 1, 10u8, 1000), 123);
+/// ```
+pub fn gibonacci_mod<M: FibModulus>(a: M, b: M, n: impl Into<u128>, m: M) -> M {
+    assert!(
+        m != M::ZERO,
+        "attempt to calculate the remainder with a divisor of zero"
+    );
+    let (x, y) = fib_pair_mod(&n.into(), m);
+    // F(n-1) = F(n+1) - F(n), which is also right for n = 0.
+    let before = y.sub_mod(x, m);
+    a.reduce(m)
+        .mul_mod(before, m)
+        .add_mod(b.reduce(m).mul_mod(x, m), m)
+}
+
+/// Calculate the n-th fibbonacci number modulo `m`, returning an error instead of panicking.
+///
+/// # Errors
+/// Returns [`FibError::ModulusZero`] if `m` is zero.
+///
+/// # Examples
+/// ```rust
+/// use quickfib::FibError;
+///
+/// assert_eq!(quickfib::try_fibbonacci_mod(10u8, 7u8), Ok(6));
+/// assert_eq!(quickfib::try_fibbonacci_mod(10u8, 0u8), Err(FibError::ModulusZero));
+/// ```
+pub fn try_fibbonacci_mod<M: FibModulus>(n: impl Into<u128>, m: M) -> Result<M, FibError> {
+    if m == M::ZERO {
+        Err(FibError::ModulusZero)
+    } else {
+        Ok(fib_pair_mod(&n.into(), m).0)
+    }
+}
+
+/// Calculate the n-th fibbonacci number modulo `m`, for an index of any size.
+///
+/// The index can be a primitive integer, a big-endian byte slice, or a big integer such
+/// as [`BigUint`](crate::BigUint); its bits are walked from the top through the
+/// doubling formulas.
+///
+/// # Panics
+/// Panics if `m` is zero.
+///
+/// # Examples
+/// ```rust
+/// // 2^128 as big-endian bytes.
+/// let mut n = [0u8; 17];
+/// n[0] = 1;
+/// let x = quickfib::fibbonacci_mod_big(&n[..], 1_000_000_007u64);
+///
+/// let y = quickfib::fibbonacci_mod_str("340282366920938463463374607431768211456", 1_000_000_007u64);
+/// assert_eq!(Ok(x), y);
+/// ```
+pub fn fibbonacci_mod_big<I: BitIndex + ?Sized, M: FibModulus>(n: &I, m: M) -> M {
+    assert!(
+        m != M::ZERO,
+        "attempt to calculate the remainder with a divisor of zero"
+    );
+    fib_pair_mod(n, m).0
+}
+
+/// Calculate the n-th fibbonacci number modulo `m`, with the index given as a decimal string.
+///
+/// The digits are consumed one at a time, so the index can have any number of digits and
+/// no allocation is needed.
+///
+/// # Errors
+/// Returns [`FibError::InvalidDigit`] if `n` is empty or contains anything but ASCII digits,
+/// and [`FibError::ModulusZero`] if `m` is zero.
+///
+/// # Examples
+/// ```rust
+/// let n = format!("1{}", "0".repeat(1000));
+/// assert_eq!(quickfib::fibbonacci_mod_str(&n, 1_000_000_007u64), Ok(552179166));
+/// ```
+pub fn fibbonacci_mod_str<M: FibModulus>(n: &str, m: M) -> Result<M, FibError> {
+    if m == M::ZERO {
+        return Err(FibError::ModulusZero);
+    }
+    if n.is_empty() {
+        return Err(FibError::InvalidDigit);
+    }
+
+    let mut pair = (M::ZERO, M::ONE.reduce(m));
+    for digit in n.bytes() {
+        if !digit.is_ascii_digit() {
+            return Err(FibError::InvalidDigit);
+        }
+        // 10k = 2 * (4k + k)
+        let four = double_mod(double_mod(pair, false, m), false, m);
+        pair = double_mod(add_mod_pairs(four, pair, m), false, m);
+        for _ in 0..digit - b'0' {
+            pair = (pair.1, pair.0.add_mod(pair.1, m));
+        }
+    }
+    Ok(pair.0)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{
+        fibbonacci_mod, fibbonacci_mod_big, fibbonacci_mod_str, gibonacci_mod, mul_wide, FibModulus,
+    };
+    use crate::{fib, FibError};
+
+    #[test]
+    fn calc_mod_small() {
+        for n in 0..=93u64 {
+            let expected = fib::<u64>(n);
+            assert_eq!(fibbonacci_mod(n, u64::MAX), expected % u64::MAX);
+            assert_eq!(fibbonacci_mod(n, 1000u16), (expected % 1000) as u16);
+            assert_eq!(fibbonacci_mod(n, 1u8), 0);
+        }
+    }
+
+    #[test]
+    fn calc_mod_huge() {
+        assert_eq!(
+            fibbonacci_mod(u128::MAX, 18446744073709551557u64),
+            431181406252422258
+        );
+        assert_eq!(
+            fibbonacci_mod(10u128.pow(30), 170141183460469231731687303715884105727u128),
+            163787675786891808641315737611817361066
+        );
+    }
+
+    #[test]
+    fn calc_mod_big_index() {
+        let m = 998_244_353u64;
+        for n in [0u128, 1, 9, 10, 99, 12345, u64::MAX as u128, u128::MAX] {
+            let expected = fibbonacci_mod(n, m);
+            assert_eq!(fibbonacci_mod_big(&n.to_be_bytes()[..], m), expected);
+            assert_eq!(fibbonacci_mod_big(&n, m), expected);
+            let mut buf = [0u8; 40];
+            let mut len = 0;
+            let mut x = n;
+            loop {
+                buf[39 - len] = b'0' + (x % 10) as u8;
+                len += 1;
+                x /= 10;
+                if x == 0 {
+                    break;
+                }
+            }
+            let s = core::str::from_utf8(&buf[40 - len..]).unwrap();
+            assert_eq!(fibbonacci_mod_str(s, m), Ok(expected));
+        }
+        assert_eq!(fibbonacci_mod_big(&[0u8; 0][..], m), 0);
+        assert_eq!(fibbonacci_mod_str("007", 100u8), Ok(13));
+        assert_eq!(fibbonacci_mod_str("", m), Err(FibError::InvalidDigit));
+        assert_eq!(fibbonacci_mod_str("12a", m), Err(FibError::InvalidDigit));
+        assert_eq!(fibbonacci_mod_str("-1", m), Err(FibError::InvalidDigit));
+        assert_eq!(fibbonacci_mod_str("1", 0u64), Err(FibError::ModulusZero));
+    }
+
+    #[test]
+    fn wide_mul_mod() {
+        assert_eq!(mul_wide(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+        let m = u128::MAX - 158;
+        assert_eq!((m - 1).mul_mod(m - 1, m), 1);
+        assert_eq!((m - 2).mul_mod(3, m), m - 6);
+    }
+
+    #[test]
+    fn gibonacci_modular() {
+        let m = 1_000_000_007u64;
+        assert_eq!(gibonacci_mod(5, 8, 1000u16, m), 90974502);
+        assert_eq!(gibonacci_mod(5, 8, 0u8, m), 5);
+        assert_eq!(gibonacci_mod(u64::MAX, 3, 0u8, m), u64::MAX % m);
+        let n = 10u128.pow(18);
+        let expected = (5 * fibbonacci_mod(n - 1, m) + 8 * fibbonacci_mod(n, m)) % m;
+        assert_eq!(gibonacci_mod(5, 8, n, m), expected);
+    }
+}
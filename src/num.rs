@@ -0,0 +1,330 @@
+use core::ops::{Add, Mul, Sub};
+
+/// Numeric types the fibbonacci algorithms can compute with.
+///
+/// Implemented for every primitive integer and float type. Implementing it for your own
+/// type only takes the two required methods; the operators are used by value and no
+/// division is needed.
+///
+/// # Examples
+/// ```rust
+/// use core::ops::{Add, Mul, Sub};
+/// use quickfib::FibNum;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct Wrapper(u64);
+///
+/// impl Add for Wrapper {
+///     type Output = Self;
+///     fn add(self, rhs: Self) -> Self { Wrapper(self.0 + rhs.0) }
+/// }
+///
+/// impl Sub for Wrapper {
+///     type Output = Self;
+///     fn sub(self, rhs: Self) -> Self { Wrapper(self.0 - rhs.0) }
+/// }
+///
+/// impl Mul for Wrapper {
+///     type Output = Self;
+///     fn mul(self, rhs: Self) -> Self { Wrapper(self.0 * rhs.0) }
+/// }
+///
+/// impl FibNum for Wrapper {
+///     fn zero() -> Self { Wrapper(0) }
+///     fn one() -> Self { Wrapper(1) }
+/// }
+///
+/// assert_eq!(quickfib::fib::<Wrapper>(20u8), Wrapper(6765));
+/// ```
+pub trait FibNum:
+    Sized + Clone + PartialEq + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    /// The additive identity.
+    fn zero() -> Self;
+
+    /// The multiplicative identity.
+    fn one() -> Self;
+
+    /// The value multiplied by two.
+    fn double(&self) -> Self {
+        self.clone() + self.clone()
+    }
+
+    /// Whether the value is zero.
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+
+    /// Whether the value is finite. Only floats can be infinite; once a doubling step
+    /// reaches infinity, the rest of the computation stays there instead of turning into
+    /// NaN through `inf - inf`.
+    fn is_finite(&self) -> bool {
+        true
+    }
+}
+
+/// Numeric types that can also serve as the index of [`fibbonacci`](crate::fibbonacci).
+///
+/// Walking the index down to zero needs halving and a parity check, which [`FibNum`]
+/// alone does not provide.
+pub trait FibIndex: FibNum {
+    /// Whether the value is divisible by two.
+    fn is_even(&self) -> bool;
+
+    /// The value divided by two, rounded towards zero.
+    fn halve(&self) -> Self;
+
+    /// Whether the value is below zero, selecting the negafibonacci sign
+    /// `F(-n) = (-1)^(n+1) F(n)`. Unsigned types keep the default.
+    fn is_negative(&self) -> bool {
+        false
+    }
+
+    /// Whether the value is a whole number. Integer types keep the default; floats
+    /// with a fractional part, infinities and NaN are rejected by
+    /// [`fibbonacci`](crate::fibbonacci).
+    fn is_integral(&self) -> bool {
+        true
+    }
+}
+
+/// Non-negative integers whose binary digits can be walked from the most significant,
+/// used as arbitrarily large indices by [`fibbonacci_mod_big`](crate::fibbonacci_mod_big).
+///
+/// Byte slices are read as big-endian numbers.
+pub trait BitIndex {
+    /// The number of significant bits, `0` for zero.
+    fn bit_len(&self) -> u64;
+
+    /// The bit of weight `2^i`.
+    fn bit(&self, i: u64) -> bool;
+}
+
+macro_rules! impl_bit_index {
+    ($($t:ty),*) => {
+        $(
+            impl BitIndex for $t {
+                #[inline]
+                fn bit_len(&self) -> u64 {
+                    u64::from(<$t>::BITS - self.leading_zeros())
+                }
+
+                #[inline]
+                fn bit(&self, i: u64) -> bool {
+                    i < u64::from(<$t>::BITS) && (self >> i) & 1 == 1
+                }
+            }
+        )*
+    };
+}
+
+impl_bit_index!(u8, u16, u32, u64, u128, usize);
+
+impl BitIndex for [u8] {
+    fn bit_len(&self) -> u64 {
+        match self.iter().position(|&b| b != 0) {
+            Some(i) => (self.len() - i) as u64 * 8 - u64::from(self[i].leading_zeros()),
+            None => 0,
+        }
+    }
+
+    fn bit(&self, i: u64) -> bool {
+        let byte = (i / 8) as usize;
+        byte < self.len() && (self[self.len() - 1 - byte] >> (i % 8)) & 1 == 1
+    }
+}
+
+macro_rules! impl_fib_num {
+    ($zero:literal, $one:literal => $($t:ty),*) => {
+        $(
+            impl FibNum for $t {
+                #[inline]
+                fn zero() -> Self {
+                    $zero
+                }
+
+                #[inline]
+                fn one() -> Self {
+                    $one
+                }
+            }
+        )*
+    };
+}
+
+impl_fib_num!(0, 1 => u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+macro_rules! impl_fib_index {
+    ($($t:ty),*) => {
+        $(
+            impl FibIndex for $t {
+                #[inline]
+                fn is_even(&self) -> bool {
+                    *self % 2 == 0
+                }
+
+                #[inline]
+                fn halve(&self) -> Self {
+                    *self / 2
+                }
+
+                #[inline]
+                #[allow(unused_comparisons)]
+                fn is_negative(&self) -> bool {
+                    *self < 0
+                }
+            }
+        )*
+    };
+}
+
+impl_fib_index!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+macro_rules! impl_fib_float {
+    ($($t:ty),*) => {
+        $(
+            impl FibNum for $t {
+                #[inline]
+                fn zero() -> Self {
+                    0.0
+                }
+
+                #[inline]
+                fn one() -> Self {
+                    1.0
+                }
+
+                #[inline]
+                fn is_finite(&self) -> bool {
+                    <$t>::is_finite(*self)
+                }
+            }
+
+            impl FibIndex for $t {
+                #[inline]
+                fn is_even(&self) -> bool {
+                    *self % 2.0 == 0.0
+                }
+
+                #[inline]
+                fn halve(&self) -> Self {
+                    // Drop the remainder first, so odd values halve exactly.
+                    (*self - *self % 2.0) / 2.0
+                }
+
+                #[inline]
+                fn is_negative(&self) -> bool {
+                    *self < 0.0
+                }
+
+                #[inline]
+                fn is_integral(&self) -> bool {
+                    *self % 1.0 == 0.0
+                }
+            }
+        )*
+    };
+}
+
+impl_fib_float!(f32, f64);
+
+#[cfg(feature = "num")]
+mod bigint {
+    use num_bigint::{BigInt, BigUint, Sign};
+    use num_traits::{One, Zero};
+
+    use super::{BitIndex, FibIndex, FibNum};
+
+    macro_rules! impl_fib_num_bigint {
+        ($($t:ty),*) => {
+            $(
+                impl FibNum for $t {
+                    fn zero() -> Self {
+                        Zero::zero()
+                    }
+
+                    fn one() -> Self {
+                        One::one()
+                    }
+
+                    fn is_zero(&self) -> bool {
+                        Zero::is_zero(self)
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_fib_num_bigint!(BigUint, BigInt);
+
+    impl FibIndex for BigUint {
+        fn is_even(&self) -> bool {
+            !self.bit(0)
+        }
+
+        fn halve(&self) -> Self {
+            self >> 1u8
+        }
+    }
+
+    impl BitIndex for BigUint {
+        fn bit_len(&self) -> u64 {
+            self.bits()
+        }
+
+        fn bit(&self, i: u64) -> bool {
+            BigUint::bit(self, i)
+        }
+    }
+
+    impl FibIndex for BigInt {
+        fn is_even(&self) -> bool {
+            !self.magnitude().bit(0)
+        }
+
+        fn halve(&self) -> Self {
+            self / 2u8
+        }
+
+        fn is_negative(&self) -> bool {
+            self.sign() == Sign::Minus
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::BitIndex;
+    #[cfg(feature = "num")]
+    use crate::{fib, fibbonacci, fibbonacci_mod, fibbonacci_mod_big};
+    #[cfg(feature = "num")]
+    use num_bigint::{BigInt, BigUint};
+
+    #[test]
+    fn bits_out_of_range() {
+        assert!(5u8.bit(2));
+        assert!(!5u8.bit(8));
+        assert!(!u8::MAX.bit(8));
+        assert!(!u128::MAX.bit(128));
+        assert!(!u64::MAX.bit(u64::MAX));
+        assert_eq!(u8::MAX.bit_len(), 8);
+    }
+
+    #[test]
+    #[cfg(feature = "num")]
+    fn calc_num_bigint() {
+        let expected: BigUint = "354224848179261915075".parse().unwrap();
+        assert_eq!(fib::<BigUint>(100u8), expected);
+        assert_eq!(fibbonacci(BigUint::from(100u8)), expected);
+        assert_eq!(
+            fibbonacci(BigInt::from(100u8)),
+            BigInt::from(expected.clone())
+        );
+        assert_eq!(fibbonacci(BigInt::from(-100)), -BigInt::from(expected));
+        assert_eq!(
+            fibbonacci_mod_big(&BigUint::from(u128::MAX), 1_000_000_007u64),
+            fibbonacci_mod(u128::MAX, 1_000_000_007u64)
+        );
+    }
+}
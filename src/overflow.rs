@@ -0,0 +1,407 @@
+use crate::FibIndex;
+
+/// Primitive integer types with well-defined overflow behaviour.
+///
+/// This trait is implemented for every primitive integer type and for the fixed-width
+/// [`Uint`](crate::Uint) types, and powers
+/// [`checked_fibbonacci`], [`wrapping_fibbonacci`], [`saturating_fibbonacci`]
+/// and [`overflowing_fibbonacci`], along with their Lucas counterparts.
+pub trait FibPrimitive: FibIndex + Copy + PartialOrd {
+    /// The smallest value of the type.
+    const MIN: Self;
+    /// The largest value of the type.
+    const MAX: Self;
+    /// The largest index `n` for which `F(n)` fits in the type.
+    const MAX_INDEX: Self;
+    /// The largest index `n` for which the Lucas number `L(n)` fits in the type.
+    const MAX_LUCAS_INDEX: Self;
+
+    /// Wrapping (modular) addition.
+    fn wrapping_add(self, rhs: Self) -> Self;
+    /// Wrapping (modular) subtraction.
+    fn wrapping_sub(self, rhs: Self) -> Self;
+    /// Wrapping (modular) multiplication.
+    fn wrapping_mul(self, rhs: Self) -> Self;
+    /// Checked addition, `None` on overflow.
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    /// Checked multiplication, `None` on overflow.
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! impl_fib_primitive {
+    ($($t:ty => $max_index:expr, $max_lucas_index:expr);* $(;)?) => {
+        $(
+            impl FibPrimitive for $t {
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+                const MAX_INDEX: Self = $max_index;
+                const MAX_LUCAS_INDEX: Self = $max_lucas_index;
+
+                #[inline]
+                fn wrapping_add(self, rhs: Self) -> Self {
+                    <$t>::wrapping_add(self, rhs)
+                }
+
+                #[inline]
+                fn wrapping_sub(self, rhs: Self) -> Self {
+                    <$t>::wrapping_sub(self, rhs)
+                }
+
+                #[inline]
+                fn wrapping_mul(self, rhs: Self) -> Self {
+                    <$t>::wrapping_mul(self, rhs)
+                }
+
+                #[inline]
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                #[inline]
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_fib_primitive! {
+    u8 => 13, 11;
+    u16 => 24, 23;
+    u32 => 47, 46;
+    u64 => 93, 92;
+    u128 => 186, 184;
+    i8 => 11, 10;
+    i16 => 23, 21;
+    i32 => 46, 44;
+    i64 => 92, 90;
+    i128 => 184, 182;
+}
+
+#[cfg(target_pointer_width = "16")]
+impl_fib_primitive! { usize => 24, 23; isize => 23, 21 }
+#[cfg(target_pointer_width = "32")]
+impl_fib_primitive! { usize => 47, 46; isize => 46, 44 }
+#[cfg(target_pointer_width = "64")]
+impl_fib_primitive! { usize => 93, 92; isize => 92, 90 }
+
+fn __wrapping_fib<T: FibPrimitive>(n: T) -> (T, T) {
+    if n.is_zero() {
+        (T::zero(), T::one())
+    } else {
+        let (a, b) = __wrapping_fib(n.halve());
+        let c = a.wrapping_mul(b.wrapping_add(b).wrapping_sub(a));
+        let d = a.wrapping_mul(a).wrapping_add(b.wrapping_mul(b));
+        if n.is_even() {
+            (c, d)
+        } else {
+            (d, c.wrapping_add(d))
+        }
+    }
+}
+
+/// The wrapped value of `F(n)`, with the negafibonacci sign for negative `n`.
+fn __wrapping_signed<T: FibPrimitive>(n: T) -> T {
+    // Halving rounds towards zero, so a negative index walks the same path as `|n|`.
+    let value = __wrapping_fib(n).0;
+    if n.is_negative() && n.is_even() {
+        T::zero().wrapping_sub(value)
+    } else {
+        value
+    }
+}
+
+/// The wrapped value of `L(n)`, with the sign `L(-n) = (-1)^n L(n)` for negative `n`.
+fn __wrapping_lucas<T: FibPrimitive>(n: T) -> T {
+    let (a, b) = __wrapping_fib(n);
+    let value = b.wrapping_add(b.wrapping_sub(a));
+    if n.is_negative() && !n.is_even() {
+        T::zero().wrapping_sub(value)
+    } else {
+        value
+    }
+}
+
+/// Whether the `n`-th term fits in `T`, given the largest index that does.
+fn __fits<T: FibPrimitive>(n: T, max_index: T) -> bool {
+    if n.is_negative() {
+        n >= T::zero() - max_index
+    } else {
+        n <= max_index
+    }
+}
+
+/// Calculate the n-th fibbonacci number, returning `None` if it does not fit in `T`.
+/// Negative indices follow `F(-n) = (-1)^(n+1) F(n)`.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::checked_fibbonacci(13u8), Some(233));
+/// assert_eq!(quickfib::checked_fibbonacci(14u8), None);
+/// assert_eq!(quickfib::checked_fibbonacci(-10i8), Some(-55));
+/// ```
+pub fn checked_fibbonacci<T: FibPrimitive>(n: T) -> Option<T> {
+    if __fits(n, T::MAX_INDEX) {
+        Some(__wrapping_signed(n))
+    } else {
+        None
+    }
+}
+
+/// Calculate the n-th fibbonacci number, wrapping around at the boundary of `T`.
+/// Negative indices follow `F(-n) = (-1)^(n+1) F(n)`.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::wrapping_fibbonacci(14u8), 121); // 377 mod 256
+/// ```
+pub fn wrapping_fibbonacci<T: FibPrimitive>(n: T) -> T {
+    __wrapping_signed(n)
+}
+
+/// Calculate the n-th fibbonacci number, saturating at `T::MAX` or `T::MIN` instead of
+/// overflowing. Negative indices follow `F(-n) = (-1)^(n+1) F(n)`.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::saturating_fibbonacci(14u8), u8::MAX);
+/// assert_eq!(quickfib::saturating_fibbonacci(-14i8), i8::MIN);
+/// ```
+pub fn saturating_fibbonacci<T: FibPrimitive>(n: T) -> T {
+    if __fits(n, T::MAX_INDEX) {
+        __wrapping_signed(n)
+    } else if n.is_negative() && n.is_even() {
+        T::MIN
+    } else {
+        T::MAX
+    }
+}
+
+/// Calculate the n-th fibbonacci number, returning the wrapped result along with
+/// a boolean indicating whether an overflow occurred.
+/// Negative indices follow `F(-n) = (-1)^(n+1) F(n)`.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::overflowing_fibbonacci(13u8), (233, false));
+/// assert_eq!(quickfib::overflowing_fibbonacci(14u8), (121, true));
+/// ```
+pub fn overflowing_fibbonacci<T: FibPrimitive>(n: T) -> (T, bool) {
+    (__wrapping_signed(n), !__fits(n, T::MAX_INDEX))
+}
+
+/// Calculate the n-th Lucas number, returning `None` if it does not fit in `T`.
+/// Negative indices follow `L(-n) = (-1)^n L(n)`.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::checked_lucas(11u8), Some(199));
+/// assert_eq!(quickfib::checked_lucas(12u8), None);
+/// assert_eq!(quickfib::checked_lucas(-9i8), Some(-76));
+/// ```
+pub fn checked_lucas<T: FibPrimitive>(n: T) -> Option<T> {
+    if __fits(n, T::MAX_LUCAS_INDEX) {
+        Some(__wrapping_lucas(n))
+    } else {
+        None
+    }
+}
+
+/// Calculate the n-th Lucas number, wrapping around at the boundary of `T`.
+/// Negative indices follow `L(-n) = (-1)^n L(n)`.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::wrapping_lucas(12u8), 66); // 322 mod 256
+/// ```
+pub fn wrapping_lucas<T: FibPrimitive>(n: T) -> T {
+    __wrapping_lucas(n)
+}
+
+/// Calculate the n-th Lucas number, saturating at `T::MAX` or `T::MIN` instead of
+/// overflowing. Negative indices follow `L(-n) = (-1)^n L(n)`.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::saturating_lucas(12u8), u8::MAX);
+/// assert_eq!(quickfib::saturating_lucas(-11i8), i8::MIN);
+/// ```
+pub fn saturating_lucas<T: FibPrimitive>(n: T) -> T {
+    if __fits(n, T::MAX_LUCAS_INDEX) {
+        __wrapping_lucas(n)
+    } else if n.is_negative() && !n.is_even() {
+        T::MIN
+    } else {
+        T::MAX
+    }
+}
+
+/// Calculate the n-th Lucas number, returning the wrapped result along with
+/// a boolean indicating whether an overflow occurred.
+/// Negative indices follow `L(-n) = (-1)^n L(n)`.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::overflowing_lucas(11u8), (199, false));
+/// assert_eq!(quickfib::overflowing_lucas(12u8), (66, true));
+/// ```
+pub fn overflowing_lucas<T: FibPrimitive>(n: T) -> (T, bool) {
+    (__wrapping_lucas(n), !__fits(n, T::MAX_LUCAS_INDEX))
+}
+
+/// Calculate the n-th term of the fibbonacci-like sequence starting from `a` and `b`,
+/// returning `None` if it does not fit in `T`.
+///
+/// The term is `a F(n-1) + b F(n)`, and `None` is also returned when one of those
+/// products overflows, even if a negative seed would bring the sum back in range.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::checked_gibonacci(3u64, 7, 89), Some(15760119247131305116));
+/// assert_eq!(quickfib::checked_gibonacci(3u64, 7, 90), None);
+/// ```
+pub fn checked_gibonacci<T: FibPrimitive>(a: T, b: T, n: T) -> Option<T> {
+    if n.is_zero() {
+        return Some(a);
+    }
+    if n == T::MIN {
+        return None;
+    }
+    // One doubling walk gives the pair `(F(n-1), F(n))`, up to sign for negative `n`.
+    let before = n - T::one();
+    let (x, y) = if n.is_negative() {
+        // With k = -n, the walk gives `(F(k), F(k+1))`, and
+        // F(n) = (-1)^(k+1) F(k) and F(n-1) = (-1)^k F(k+1).
+        let (x, y) = __wrapping_fib(n);
+        if n.is_even() {
+            (y, T::zero().wrapping_sub(x))
+        } else {
+            (T::zero().wrapping_sub(y), x)
+        }
+    } else {
+        __wrapping_fib(before)
+    };
+    // A zero seed leaves its fibbonacci number out, which may not fit on its own.
+    let term = |seed: T, value: T, index: T| {
+        if seed.is_zero() {
+            Some(T::zero())
+        } else if __fits(index, T::MAX_INDEX) {
+            value.checked_mul(seed)
+        } else {
+            None
+        }
+    };
+    term(a, x, before)?.checked_add(term(b, y, n)?)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{
+        checked_fibbonacci, checked_gibonacci, checked_lucas, overflowing_fibbonacci,
+        overflowing_lucas, saturating_fibbonacci, saturating_lucas, wrapping_fibbonacci,
+        wrapping_lucas,
+    };
+
+    #[test]
+    fn checked_limits() {
+        assert_eq!(checked_fibbonacci(13u8), Some(233));
+        assert_eq!(checked_fibbonacci(14u8), None);
+        assert_eq!(checked_fibbonacci(11i8), Some(89));
+        assert_eq!(checked_fibbonacci(12i8), None);
+        assert_eq!(checked_fibbonacci(-1i8), Some(1));
+        assert_eq!(checked_fibbonacci(-11i8), Some(89));
+        assert_eq!(checked_fibbonacci(-12i8), None);
+        assert_eq!(checked_fibbonacci(i8::MIN), None);
+        assert_eq!(checked_fibbonacci(93u64), Some(12200160415121876738));
+        assert_eq!(checked_fibbonacci(94u64), None);
+        assert_eq!(
+            checked_fibbonacci(186u128),
+            Some(332825110087067562321196029789634457848)
+        );
+        assert_eq!(checked_fibbonacci(187u128), None);
+    }
+
+    #[test]
+    fn wrapping_matches_modulus() {
+        // F(100) mod 2^64
+        let expected = (354224848179261915075u128 % (1u128 << 64)) as u64;
+        assert_eq!(wrapping_fibbonacci(100u64), expected);
+        assert_eq!(overflowing_fibbonacci(100u64), (expected, true));
+    }
+
+    #[test]
+    fn saturating_limits() {
+        assert_eq!(saturating_fibbonacci(46i32), 1836311903);
+        assert_eq!(saturating_fibbonacci(47i32), i32::MAX);
+        assert_eq!(saturating_fibbonacci(1000u16), u16::MAX);
+        assert_eq!(saturating_fibbonacci(-46i32), -1836311903);
+        assert_eq!(saturating_fibbonacci(-47i32), i32::MAX);
+        assert_eq!(saturating_fibbonacci(-48i32), i32::MIN);
+    }
+
+    #[test]
+    fn negafibonacci() {
+        let expected = [0, 1, -1, 2, -3, 5, -8, 13, -21, 34];
+        for (n, value) in expected.into_iter().enumerate() {
+            assert_eq!(wrapping_fibbonacci(-(n as i64)), value);
+            assert_eq!(overflowing_fibbonacci(-(n as i64)), (value, false));
+        }
+        // F(n) = F(n+2) - F(n+1) holds modulo 2^64 all the way down.
+        let (a, b) = (
+            wrapping_fibbonacci(i64::MIN + 1),
+            wrapping_fibbonacci(i64::MIN + 2),
+        );
+        assert_eq!(wrapping_fibbonacci(i64::MIN), b.wrapping_sub(a));
+    }
+
+    #[test]
+    fn lucas_limits() {
+        assert_eq!(checked_lucas(11i8), None);
+        assert_eq!(checked_lucas(10i8), Some(123));
+        assert_eq!(checked_lucas(-10i8), Some(123));
+        assert_eq!(checked_lucas(92u64), Some(16860207025497407047));
+        assert_eq!(checked_lucas(93u64), None);
+        assert_eq!(checked_lucas(-90i64), Some(6440026026380244498));
+        assert_eq!(checked_lucas(-91i64), None);
+        assert_eq!(
+            checked_lucas(184u128),
+            Some(284266580942632122201475224120405260207)
+        );
+        assert_eq!(checked_lucas(185u128), None);
+        assert_eq!(saturating_lucas(-91i64), i64::MIN);
+        assert_eq!(saturating_lucas(-92i64), i64::MAX);
+        // L(100) mod 2^64
+        let expected = 792070839848372253127u128 % (1 << 64);
+        assert_eq!(wrapping_lucas(100u64), expected as u64);
+        assert_eq!(overflowing_lucas(100u64), (expected as u64, true));
+        for n in -30..=30i32 {
+            assert_eq!(wrapping_lucas(n), checked_lucas(n).unwrap());
+        }
+    }
+
+    #[test]
+    fn gibonacci_limits() {
+        assert_eq!(checked_gibonacci(3u64, 7, 0), Some(3));
+        assert_eq!(checked_gibonacci(3u64, 7, 89), Some(15760119247131305116));
+        assert_eq!(checked_gibonacci(3u64, 7, 90), None);
+        assert_eq!(checked_gibonacci(1u64, 0, 94), Some(12200160415121876738));
+        assert_eq!(checked_gibonacci(2u64, 1, 92), Some(16860207025497407047));
+        assert_eq!(checked_gibonacci(3i32, 7, -5), Some(11));
+        assert_eq!(checked_gibonacci(3i8, 7, i8::MIN), None);
+        assert_eq!(checked_gibonacci(0i8, 1, -11), Some(89));
+        assert_eq!(checked_gibonacci(1i8, 0, -10), Some(89));
+        assert_eq!(checked_gibonacci(1i8, 0, -11), None);
+        assert_eq!(checked_gibonacci(0i8, 1, 12), None);
+        for n in -40..=40i64 {
+            assert_eq!(checked_gibonacci(0, 1, n), checked_fibbonacci(n));
+            assert_eq!(checked_gibonacci(2, 1, n), checked_lucas(n));
+            let (mut x, mut y) = (3i64, 7i64);
+            for _ in 0..n.unsigned_abs() {
+                (x, y) = if n > 0 { (y, x + y) } else { (y - x, x) };
+            }
+            assert_eq!(checked_gibonacci(3, 7, n), Some(x));
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use crate::factor::{lcm_u128, Factors};
+use crate::modular::fib_pair_mod;
+
+/// Whether the fibbonacci sequence modulo `m` repeats after `d` terms.
+fn is_period(d: u128, m: u64) -> bool {
+    fib_pair_mod(&d, m) == (0, 1 % m)
+}
+
+/// Shrink `d` to the smallest divisor still satisfying `holds`, by dividing out `primes`.
+/// `holds` must be true exactly on the multiples of some divisor of `d`, and `primes` must
+/// include every prime factor of `d`.
+fn shrink(mut d: u128, primes: impl Iterator<Item = u64>, holds: impl Fn(u128) -> bool) -> u128 {
+    for q in primes {
+        let q = u128::from(q);
+        while d.is_multiple_of(q) && holds(d / q) {
+            d /= q;
+        }
+    }
+    d
+}
+
+/// The entry point of the prime `p`, which divides `p - (5/p)` for `p != 2, 5`.
+/// `primes_of(d)` lists the prime factors of `d`, and `holds(z)` checks whether `p`
+/// divides `F(z)`.
+pub(crate) fn prime_entry_point_with<I: Iterator<Item = u64>>(
+    p: u64,
+    primes_of: impl FnOnce(u64) -> I,
+    holds: impl Fn(u128) -> bool,
+) -> u128 {
+    let d = match p {
+        2 => return 3,
+        5 => return 5,
+        _ if matches!(p % 5, 1 | 4) => p - 1,
+        _ => p + 1,
+    };
+    shrink(d.into(), primes_of(d), holds)
+}
+
+/// The pisano period of the prime `p`.
+fn prime_period(p: u64) -> u128 {
+    match p {
+        2 => 3,
+        5 => 20,
+        // π(p) divides p - 1 when p ≡ ±1 (mod 5), and 2(p + 1) when p ≡ ±2 (mod 5).
+        _ if matches!(p % 5, 1 | 4) => {
+            let factors = Factors::of(p - 1);
+            shrink(u128::from(p - 1), factors.primes(), |d| is_period(d, p))
+        }
+        _ => {
+            let factors = Factors::of(p + 1);
+            let primes = core::iter::once(2).chain(factors.primes());
+            shrink(2 * (u128::from(p) + 1), primes, |d| is_period(d, p))
+        }
+    }
+}
+
+/// The pisano period of the prime power `p^k`, which is `π(p) * p^j` for some `j < k`.
+fn prime_power_period(p: u64, k: u32) -> u128 {
+    let mut period = prime_period(p);
+    if k > 1 {
+        let pk = p.pow(k);
+        while !is_period(period, pk) {
+            period *= u128::from(p);
+        }
+    }
+    period
+}
+
+/// Calculate the pisano period π(m), the period of the fibbonacci numbers modulo `m`.
+///
+/// The period is computed from the factorisation of `m`: the period of each prime is
+/// found among the divisors of `p - 1` or `2(p + 1)`, lifted to the prime power, and the
+/// results are combined with their least common multiple. The result can exceed `u64`,
+/// since π(m) can be as large as `6m`.
+///
+/// # Panics
+/// Panics if `m` is zero.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::pisano_period(10), 60);
+/// assert_eq!(quickfib::pisano_period(1_000_000_007), 2_000_000_016);
+/// ```
+pub fn pisano_period(m: u64) -> u128 {
+    assert!(m != 0, "pisano period of zero");
+    Factors::of(m)
+        .iter()
+        .map(|(p, k)| prime_power_period(p, k))
+        .fold(1, lcm_u128)
+}
+
+/// Whether `m` divides `F(d)`.
+fn divides(d: u128, m: u64) -> bool {
+    fib_pair_mod(&d, m).0 == 0
+}
+
+/// The entry point of the prime power `p^k`, which is `z(p) * p^j` for some `j < k`.
+fn prime_power_entry_point(p: u64, k: u32) -> u128 {
+    let mut z = prime_entry_point_with(p, |d| Factors::of(d).into_primes(), |z| divides(z, p));
+    if k > 1 {
+        let pk = p.pow(k);
+        while !divides(z, pk) {
+            z *= u128::from(p);
+        }
+    }
+    z
+}
+
+/// Calculate the entry point z(m), also called the rank of apparition: the smallest
+/// `n > 0` such that `m` divides `F(n)`.
+///
+/// Like [`pisano_period`], it works from the factorisation of `m`: the entry point of
+/// each prime `p` divides `p - 1` or `p + 1` depending on `p mod 5`, and the candidates
+/// are checked with modular fast doubling. The result can exceed `u64`, since z(m) can be
+/// as large as `2m`.
+///
+/// # Panics
+/// Panics if `m` is zero.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::entry_point(10), 15);
+/// assert_eq!(quickfib::entry_point(1_000_000_007), 1_000_000_008);
+/// ```
+pub fn entry_point(m: u64) -> u128 {
+    assert!(m != 0, "entry point of zero");
+    Factors::of(m)
+        .iter()
+        .map(|(p, k)| prime_power_entry_point(p, k))
+        .fold(1, lcm_u128)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{entry_point, pisano_period};
+    use crate::fibbonacci_mod;
+
+    fn brute_force(m: u64) -> u128 {
+        let (mut a, mut b) = (0, 1 % m);
+        let mut n = 0;
+        loop {
+            (a, b) = (b, (a + b) % m);
+            n += 1;
+            if (a, b) == (0, 1 % m) {
+                return n;
+            }
+        }
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        for m in 1..=500 {
+            assert_eq!(pisano_period(m), brute_force(m), "m = {}", m);
+        }
+        assert_eq!(pisano_period(5u64.pow(6)), brute_force(5u64.pow(6)));
+    }
+
+    #[test]
+    fn large_moduli() {
+        for m in [u64::MAX, 18446744073709551557, 1 << 63, 999_999_999_989 * 7] {
+            let period = pisano_period(m);
+            assert_eq!(fibbonacci_mod(period, m), 0);
+            assert_eq!(fibbonacci_mod(period + 1, m), 1 % m);
+        }
+        assert_eq!(pisano_period(1 << 63), 3 << 62);
+    }
+
+    #[test]
+    fn entry_points() {
+        for m in 1..=500u64 {
+            let (mut a, mut b, mut n) = (1 % m, 1 % m, 1);
+            while a != 0 {
+                (a, b) = (b, (a + b) % m);
+                n += 1;
+            }
+            assert_eq!(entry_point(m), n, "m = {}", m);
+        }
+        for m in [u64::MAX, 18446744073709551557, 1 << 63, 999_999_999_989 * 7] {
+            let z = entry_point(m);
+            assert_eq!(fibbonacci_mod(z, m), 0);
+            assert_eq!(pisano_period(m) % z, 0);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+#[cfg(feature = "std")]
+use core::f64::consts::PI;
+
+use crate::fib;
+
+/// The golden ratio φ.
+const PHI: f64 = 1.618_033_988_749_895;
+/// √5.
+#[cfg(feature = "std")]
+const SQRT_5: f64 = 2.236_067_977_499_79;
+/// φ² / √5, which keeps the powers of φ below `f64::MAX` up to `F(1476)`.
+const PHI_SQ_OVER_SQRT_5: f64 = 1.170_820_393_249_936_9;
+
+/// Calculate the fibbonacci function at a real argument, with the continuous extension of
+/// Binet's formula `(φ^x - cos(πx) φ^-x) / √5`.
+///
+/// It agrees with the fibbonacci numbers at every integer, negative ones included, up to
+/// the rounding of `f64`, and interpolates smoothly between them. Use [`fibbonacci`](crate::fibbonacci)
+/// for exact results at integers.
+///
+/// # Examples
+/// ```rust
+/// assert!((quickfib::fib_real(10.0) - 55.0).abs() < 1e-9);
+/// assert!((quickfib::fib_real(0.5) - 0.5688644810057831).abs() < 1e-12);
+/// ```
+#[cfg(feature = "std")]
+pub fn fib_real(x: f64) -> f64 {
+    (PHI.powf(x) - (PI * x).cos() * PHI.powf(-x)) / SQRT_5
+}
+
+/// Approximate the n-th fibbonacci number as an `f64`, returning the value together with
+/// a bound on its relative error.
+///
+/// Indices up to 93 are computed exactly and rounded once. Larger ones use Binet's
+/// formula as `φ^(n-2) · φ²/√5`, with the power taken by repeated squaring, so the
+/// result stays finite up to `F(1476) ≈ 1.3e308` and needs no `std`. The rounding of φ
+/// is amplified by the exponent, so the bound grows linearly with `n`, to about
+/// `3.3e-13` at `n = 1476`. Larger indices return infinity.
+///
+/// # Examples
+/// ```rust
+/// let (value, bound) = quickfib::fib_approx_f64(1000u32);
+/// let expected = 4.3466557686937455e208;
+/// assert!((value - expected).abs() <= bound * expected);
+/// ```
+pub fn fib_approx_f64(n: impl Into<u64>) -> (f64, f64) {
+    let n = n.into();
+    if n <= 93 {
+        // F(78) is the last value below 2^53, which converts exactly.
+        let bound = if n <= 78 { 0.0 } else { f64::EPSILON / 2.0 };
+        return (fib::<u64>(n) as f64, bound);
+    }
+
+    let k = n - 2;
+    let mut value = PHI_SQ_OVER_SQRT_5;
+    let mut base = PHI;
+    for i in 0..u64::BITS - k.leading_zeros() {
+        if (k >> i) & 1 == 1 {
+            value *= base;
+        }
+        base *= base;
+    }
+    // Each step and each rounded constant costs at most half an epsilon; the rounding
+    // of φ is raised to the power k.
+    let steps = 2 * (u64::BITS - k.leading_zeros()) + 2;
+    let bound = (n as f64 + f64::from(steps)) * f64::EPSILON;
+    (value, bound)
+}
+
+/// Approximate the n-th fibbonacci number as an `f32`, returning the value together with
+/// a bound on its relative error.
+///
+/// The value is [`fib_approx_f64`] rounded to `f32`, so the bound is dominated by that
+/// final rounding. The result stays finite up to `F(186) ≈ 3.3e38`.
+///
+/// # Examples
+/// ```rust
+/// let (value, bound) = quickfib::fib_approx_f32(150u32);
+/// let expected = 9.969217e30f32;
+/// assert!((value - expected).abs() <= bound * expected);
+/// ```
+pub fn fib_approx_f32(n: impl Into<u64>) -> (f32, f32) {
+    let (value, bound) = fib_approx_f64(n);
+    (value as f32, bound as f32 + f32::EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[cfg(feature = "std")]
+    use super::fib_real;
+    use super::{fib_approx_f32, fib_approx_f64};
+    use crate::fib;
+    #[cfg(feature = "std")]
+    use crate::fibbonacci;
+
+    #[test]
+    fn approx_within_bound() {
+        for n in 0..=186u32 {
+            let expected = fib::<u128>(n);
+            let (value, bound) = fib_approx_f64(n);
+            let error = (value - expected as f64).abs();
+            assert!(error <= bound * expected as f64, "n = {}", n);
+            let (value, bound) = fib_approx_f32(n);
+            let error = (f64::from(value) - expected as f64).abs();
+            assert!(error <= f64::from(bound) * expected as f64, "n = {}", n);
+        }
+        assert_eq!(fib_approx_f64(78u8), (8944394323791464.0, 0.0));
+        for (n, expected) in [
+            (500u32, 1.3942322456169787e104),
+            (1000, 4.3466557686937455e208),
+            (1476, 1.3069892237633993e308),
+        ] {
+            let (value, bound) = fib_approx_f64(n);
+            assert!((value - expected).abs() <= bound * expected, "n = {}", n);
+        }
+        assert_eq!(fib_approx_f64(1477u32).0, f64::INFINITY);
+        assert_eq!(fib_approx_f64(u64::MAX).0, f64::INFINITY);
+        assert!(fib_approx_f32(186u8).0.is_finite());
+        assert_eq!(fib_approx_f32(187u8).0, f32::INFINITY);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn matches_integers() {
+        for n in -40..=70i64 {
+            let expected = fibbonacci(n) as f64;
+            let error = (fib_real(n as f64) - expected).abs();
+            assert!(error <= expected.abs() * 1e-13 + 1e-13, "n = {}", n);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn interpolates() {
+        for (x, expected) in [
+            (0.5, 0.5688644810057831),
+            (1.5, 0.920442065259926),
+            (2.5, 1.4893065462657091),
+            (-0.5, 0.35157758425414287),
+            (-1.5, 0.21728689675164034),
+        ] {
+            assert!((fib_real(x) - expected).abs() < 1e-12, "x = {}", x);
+        }
+        // The recurrence still holds between the integers.
+        let x = 3.7;
+        assert!((fib_real(x + 2.0) - fib_real(x + 1.0) - fib_real(x)).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,332 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::kitamasa::power_of_x;
+use crate::{BitIndex, FibError, FibModulus, FibNum};
+
+/// A constant-coefficient linear recurrence `a(n) = c[0] a(n-1) + c[1] a(n-2) + ... + c[k-1] a(n-k)`,
+/// together with its initial terms `a(0), ..., a(k-1)`.
+///
+/// The n-th term is computed with Kitamasa's method: `x^n` is reduced modulo the
+/// characteristic polynomial `x^k - c[0] x^(k-1) - ... - c[k-1]` by repeated squaring, and
+/// its coefficients weight the initial terms. That takes `O(k^2 log n)` operations, over
+/// any [`FibNum`] or modulo `m`.
+///
+/// # Examples
+/// ```rust
+/// use quickfib::LinearRecurrence;
+///
+/// // a(n) = a(n-1) + 2a(n-3), from 1, 1, 1.
+/// let rec = LinearRecurrence::new(&[1u64, 0, 2], &[1, 1, 1]);
+/// assert_eq!(rec.nth(6u8), 13);
+/// assert_eq!(rec.nth_mod(1_000_000_000_000u64, 1_000_000_007), 720192359);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinearRecurrence<T> {
+    coefficients: Box<[T]>,
+    initial: Box<[T]>,
+}
+
+impl<T: Clone> LinearRecurrence<T> {
+    /// Create the recurrence from its coefficients `c[0], ..., c[k-1]`, where `c[i]`
+    /// multiplies `a(n-1-i)`, and its initial terms `a(0), ..., a(k-1)`.
+    ///
+    /// # Panics
+    /// Panics if the slices are empty or differ in length.
+    pub fn new(coefficients: &[T], initial: &[T]) -> Self {
+        assert!(!coefficients.is_empty(), "empty linear recurrence");
+        assert_eq!(
+            coefficients.len(),
+            initial.len(),
+            "one initial term is needed per coefficient"
+        );
+        LinearRecurrence {
+            coefficients: coefficients.into(),
+            initial: initial.into(),
+        }
+    }
+
+    /// The order `k` of the recurrence.
+    pub fn order(&self) -> usize {
+        self.coefficients.len()
+    }
+}
+
+impl<T: FibNum> LinearRecurrence<T> {
+    /// Calculate the n-th term.
+    /// The function may panic if the type T is not large enough to hold the intermediate
+    /// values, the coefficients of `x^n` modulo the characteristic polynomial.
+    pub fn nth(&self, n: impl Into<u128>) -> T {
+        let n = n.into();
+        if let Some(term) = usize::try_from(n).ok().and_then(|i| self.initial.get(i)) {
+            return term.clone();
+        }
+        let mut scratch = vec![T::zero(); self.order()];
+        let mut c = scratch.clone();
+        c[0] = T::one();
+        let c = power_of_x(
+            n,
+            &self.coefficients,
+            &mut c,
+            &mut scratch,
+            T::zero(),
+            |a, b| a + b,
+            |a, b| a * b,
+        );
+        c.iter()
+            .zip(self.initial.iter())
+            .fold(T::zero(), |sum, (c, a)| sum + c.clone() * a.clone())
+    }
+}
+
+impl<M: FibModulus> LinearRecurrence<M> {
+    /// Calculate the n-th term modulo `m`. The coefficients and initial terms are reduced
+    /// modulo `m` first, and intermediate products are widened, so the function never
+    /// overflows.
+    ///
+    /// # Panics
+    /// Panics if `m` is zero.
+    pub fn nth_mod(&self, n: impl Into<u128>, m: M) -> M {
+        assert!(
+            m != M::ZERO,
+            "attempt to calculate the remainder with a divisor of zero"
+        );
+        let coefficients: Vec<M> = self.coefficients.iter().map(|c| c.reduce(m)).collect();
+        let mut scratch = vec![M::ZERO; self.order()];
+        let mut c = scratch.clone();
+        c[0] = M::ONE.reduce(m);
+        let c = power_of_x(
+            n.into(),
+            &coefficients,
+            &mut c,
+            &mut scratch,
+            M::ZERO,
+            |a, b| a.add_mod(b, m),
+            |a, b| a.mul_mod(b, m),
+        );
+        c.iter()
+            .zip(self.initial.iter())
+            .fold(M::ZERO, |sum, (c, a)| {
+                sum.add_mod(c.mul_mod(a.reduce(m), m), m)
+            })
+    }
+}
+
+/// Find the shortest linear recurrence generating `terms` modulo the prime `p`, with the
+/// Berlekamp-Massey algorithm.
+///
+/// The result reproduces every given term and can evaluate far ones with
+/// [`LinearRecurrence::nth_mod`]. A recurrence of order `k` is only determined by at
+/// least `2k` terms, so pass twice as many terms as the order you expect. A sequence of
+/// zeros gives the order 1 recurrence `a(n) = 0`.
+///
+/// # Errors
+/// Returns [`FibError::ModulusZero`] if `p` is zero.
+///
+/// # Examples
+/// ```rust
+/// let p = 1_000_000_007u64;
+/// let squares: Vec<u64> = (0..10).map(|n| n * n).collect();
+/// let rec = quickfib::berlekamp_massey(&squares, p).unwrap();
+/// assert_eq!(rec.order(), 3);
+/// assert_eq!(rec.nth_mod(1000u16, p), 1_000_000);
+/// ```
+pub fn berlekamp_massey<M: FibModulus + BitIndex>(
+    terms: &[M],
+    p: M,
+) -> Result<LinearRecurrence<M>, FibError> {
+    if p == M::ZERO {
+        return Err(FibError::ModulusZero);
+    }
+    let terms: Vec<M> = terms.iter().map(|t| t.reduce(p)).collect();
+    let one = M::ONE.reduce(p);
+    // d^-1 = d^(p-2) by Fermat's little theorem.
+    let exponent = M::ZERO.sub_mod(one.add_mod(one, p), p);
+    let inverse = |d: M| {
+        let mut result = one;
+        for i in (0..exponent.bit_len()).rev() {
+            result = result.mul_mod(result, p);
+            if exponent.bit(i) {
+                result = result.mul_mod(d, p);
+            }
+        }
+        result
+    };
+
+    // The connection polynomial `1 + c[1] x + ... + c[len] x^len`, and the one before the
+    // last length change.
+    let mut current = vec![one];
+    let mut previous = vec![one];
+    let mut len = 0;
+    let mut shift = 1;
+    let mut last_discrepancy = one;
+    for n in 0..terms.len() {
+        let discrepancy = (1..=len).fold(terms[n], |d, i| {
+            d.add_mod(current[i].mul_mod(terms[n - i], p), p)
+        });
+        if discrepancy == M::ZERO {
+            shift += 1;
+            continue;
+        }
+
+        let factor = discrepancy.mul_mod(inverse(last_discrepancy), p);
+        let before = current.clone();
+        if current.len() < previous.len() + shift {
+            current.resize(previous.len() + shift, M::ZERO);
+        }
+        for (i, &b) in previous.iter().enumerate() {
+            current[i + shift] = current[i + shift].sub_mod(factor.mul_mod(b, p), p);
+        }
+        if 2 * len <= n {
+            len = n + 1 - len;
+            previous = before;
+            last_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+    }
+
+    if len == 0 {
+        return Ok(LinearRecurrence::new(&[M::ZERO], &[M::ZERO]));
+    }
+    current.resize(len + 1, M::ZERO);
+    let coefficients: Vec<M> = current[1..]
+        .iter()
+        .map(|&c| M::ZERO.sub_mod(c, p))
+        .collect();
+    Ok(LinearRecurrence::new(&coefficients, &terms[..len]))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{berlekamp_massey, LinearRecurrence};
+    use crate::{fib, fibbonacci_mod, kbonacci, kbonacci_mod, pell, BigUint, FibError};
+    use alloc::vec::Vec;
+
+    fn naive(coefficients: &[i128], initial: &[i128], n: usize) -> i128 {
+        let mut a = initial.to_vec();
+        while a.len() <= n {
+            let len = a.len();
+            let next = coefficients
+                .iter()
+                .enumerate()
+                .map(|(i, c)| c * a[len - 1 - i])
+                .sum();
+            a.push(next);
+        }
+        a[n]
+    }
+
+    #[test]
+    fn matches_naive() {
+        let cases: [(&[i128], &[i128]); 5] = [
+            (&[1, 1], &[0, 1]),
+            (&[2, -1], &[3, 5]),
+            (&[0, 0, 1], &[4, -2, 7]),
+            (&[1, -3, 2, 5], &[1, 0, -1, 2]),
+            (&[-2], &[3]),
+        ];
+        for (coefficients, initial) in cases {
+            let rec = LinearRecurrence::new(coefficients, initial);
+            assert_eq!(rec.order(), coefficients.len());
+            for n in 0..=40u8 {
+                assert_eq!(
+                    rec.nth(n),
+                    naive(coefficients, initial, n.into()),
+                    "{:?}, n = {}",
+                    coefficients,
+                    n
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn matches_named_sequences() {
+        let fibbonacci = LinearRecurrence::new(&[1u64, 1], &[0, 1]);
+        let tribonacci = LinearRecurrence::new(&[1u64, 1, 1], &[0, 0, 1]);
+        let pell_numbers = LinearRecurrence::new(&[2u64, 1], &[0, 1]);
+        for n in 0..=70u8 {
+            assert_eq!(fibbonacci.nth(n), fib::<u64>(n));
+            assert_eq!(tribonacci.nth(n), kbonacci::<3, u64>(n));
+            if n <= 51 {
+                assert_eq!(pell_numbers.nth(n), pell(u64::from(n)));
+            }
+        }
+        let big = LinearRecurrence::new(
+            &[BigUint::from(1u8), BigUint::from(1u8)],
+            &[BigUint::from(0u8), BigUint::from(1u8)],
+        );
+        assert_eq!(big.nth(1000u16), fib::<BigUint>(1000u16));
+    }
+
+    #[test]
+    fn modular() {
+        let m = 998_244_353u64;
+        let fibbonacci = LinearRecurrence::new(&[1u64, 1], &[0, 1]);
+        let pentanacci = LinearRecurrence::new(&[1u64; 5], &[0, 0, 0, 0, 1]);
+        for n in [0u128, 1, 5, 1000, 10u128.pow(18), u128::MAX] {
+            assert_eq!(fibbonacci.nth_mod(n, m), fibbonacci_mod(n, m));
+            assert_eq!(pentanacci.nth_mod(n, m), kbonacci_mod::<5, u64>(n, m));
+        }
+        // Coefficients and initial terms above the modulus are reduced.
+        let rec = LinearRecurrence::new(&[u64::MAX, 3], &[u64::MAX, 7]);
+        let reduced = LinearRecurrence::new(&[u64::MAX % m, 3], &[u64::MAX % m, 7]);
+        assert_eq!(rec.nth_mod(12345u16, m), reduced.nth_mod(12345u16, m));
+        assert_eq!(rec.nth_mod(0u8, 10), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "one initial term is needed per coefficient")]
+    fn mismatched_lengths() {
+        LinearRecurrence::new(&[1, 1], &[0]);
+    }
+
+    #[test]
+    fn finds_shortest_recurrence() {
+        let p = 1_000_000_007u64;
+        let fibbonacci: Vec<u64> = (0..20u8).map(fib).collect();
+        let rec = berlekamp_massey(&fibbonacci, p).unwrap();
+        assert_eq!(rec, LinearRecurrence::new(&[1, 1], &[0, 1]));
+        assert_eq!(
+            rec.nth_mod(10u128.pow(18), p),
+            fibbonacci_mod(10u128.pow(18), p)
+        );
+
+        let tribonacci: Vec<u64> = (0..12u8).map(kbonacci::<3, u64>).collect();
+        let rec = berlekamp_massey(&tribonacci, p).unwrap();
+        assert_eq!(rec.order(), 3);
+        assert_eq!(
+            rec.nth_mod(u128::MAX, p),
+            kbonacci_mod::<3, u64>(u128::MAX, p)
+        );
+
+        // n^3 + 2^n mod p, of order 5.
+        let terms: Vec<u64> = (0..10u32).map(|n| u64::from(n).pow(3) + (1 << n)).collect();
+        let rec = berlekamp_massey(&terms, p).unwrap();
+        assert_eq!(rec.order(), 5);
+        for n in 0..40u32 {
+            let expected = (u128::from(n).pow(3) + (1u128 << n)) % u128::from(p);
+            assert_eq!(u128::from(rec.nth_mod(n, p)), expected);
+        }
+    }
+
+    #[test]
+    fn degenerate_prefixes() {
+        let rec = berlekamp_massey(&[0u64; 6], 13).unwrap();
+        assert_eq!(rec.nth_mod(100u8, 13), 0);
+        let rec = berlekamp_massey(&[0u64, 0, 5], 13).unwrap();
+        assert_eq!(rec.order(), 3);
+        assert_eq!(
+            (0..3u8).map(|n| rec.nth_mod(n, 13)).collect::<Vec<_>>(),
+            [0, 0, 5]
+        );
+        let rec = berlekamp_massey(&[1u8, 0, 1, 0, 1, 0], 2).unwrap();
+        assert_eq!(rec.nth_mod(101u8, 2), 0);
+        assert_eq!(rec.nth_mod(100u8, 2), 1);
+        assert_eq!(berlekamp_massey(&[1u8], 0), Err(FibError::ModulusZero));
+    }
+}
@@ -0,0 +1,418 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::{FibError, FibIndex, FibModulus, FibNum};
+
+/// Map the pair `(U(k), U(k+1))` to the pair at `2k`, or at `2k+1` if `odd`, where `Q` is
+/// `q`, or `-q` if `q_negative`. Negating `Q` up front keeps every intermediate of the
+/// Pell and Jacobsthal numbers non-negative, so unsigned types work too.
+fn lucas_double<T: FibNum>((a, b): (T, T), odd: bool, p: &T, q: &T, q_negative: bool) -> (T, T) {
+    // U(2k) = U(k) V(k) and U(2k+1) = U(k+1)^2 - Q U(k)^2.
+    let c = a.clone() * (b.double() - p.clone() * a.clone());
+    let d = minus_q(b.clone() * b, a.clone() * a, q, q_negative);
+    if odd {
+        let e = minus_q(p.clone() * d.clone(), c, q, q_negative);
+        (d, e)
+    } else {
+        (c, d)
+    }
+}
+
+/// Map the pair `(U(k), U(k+1))` to `U(2k)`, or to `U(2k+1)` if `odd`.
+fn lucas_last<T: FibNum>((a, b): (T, T), odd: bool, p: &T, q: &T, q_negative: bool) -> T {
+    if odd {
+        minus_q(b.clone() * b, a.clone() * a, q, q_negative)
+    } else {
+        a.clone() * (b.double() - p.clone() * a)
+    }
+}
+
+/// `x - Q y`.
+#[inline]
+fn minus_q<T: FibNum>(x: T, y: T, q: &T, q_negative: bool) -> T {
+    if q_negative {
+        x + q.clone() * y
+    } else {
+        x - q.clone() * y
+    }
+}
+
+/// The pair `(U(|n|), U(|n|+1))`, walking the index down by halving.
+fn lucas_pair_index<T: FibIndex>(n: &T, p: &T, q: &T, q_negative: bool) -> (T, T) {
+    if n.is_zero() {
+        (T::zero(), T::one())
+    } else {
+        let pair = lucas_pair_index(&n.halve(), p, q, q_negative);
+        lucas_double(pair, !n.is_even(), p, q, q_negative)
+    }
+}
+
+/// `U(|n|)` for `Q = -r`, without computing `U(|n|+1)`.
+fn lucas_u_index<T: FibIndex>(n: &T, p: &T, r: &T) -> T {
+    assert!(n.is_integral(), "non-integral fibbonacci index");
+    if n.is_zero() {
+        return T::zero();
+    }
+    let pair = lucas_pair_index(&n.halve(), p, r, true);
+    lucas_last(pair, !n.is_even(), p, r, true)
+}
+
+/// The pair `(U(n), U(n+1))` of the Lucas sequence with parameters `P` and `Q`,
+/// walking the bits of a machine-integer index.
+fn lucas_pair<T: FibNum>(p: &T, q: &T, n: u64) -> (T, T) {
+    let mut pair = (T::zero(), T::one());
+    for i in (0..u64::BITS - n.leading_zeros()).rev() {
+        pair = lucas_double(pair, (n >> i) & 1 == 1, p, q, false);
+    }
+    pair
+}
+
+/// `V(n) = 2U(n+1) - P U(n)`, ordered so that no intermediate goes negative.
+fn lucas_v<T: FibNum>(p: &T, q_negative: bool, (a, b): (T, T)) -> T {
+    if q_negative {
+        // U(n+1) - P U(n) = -Q U(n-1) is non-negative here.
+        b.clone() + (b - p.clone() * a)
+    } else {
+        b.double() - p.clone() * a
+    }
+}
+
+/// Calculate the n-th terms `(U(n), V(n))` of the Lucas sequences with parameters `P` and `Q`.
+/// The function may panic if the type T is not large enough to hold the intermediate
+/// values, which reach a small multiple of `U(n+1)`.
+///
+/// The sequences start at `U(0) = 0, U(1) = 1` and `V(0) = 2, V(1) = P`, and both follow
+/// `X(n+2) = P X(n+1) - Q X(n)`. `P = 1, Q = -1` gives the fibbonacci and Lucas numbers,
+/// `P = 2, Q = -1` the Pell numbers and `P = 3, Q = 2` the Mersenne numbers `2^n - 1`.
+/// The terms are computed with the division-free doubling formulas
+///
+/// ```math
+/// U(2k) = U(k) * (2 * U(k+1) - P * U(k))
+/// U(2k+1) = U(k+1)^2 - Q * U(k)^2
+/// ```
+///
+/// # Examples
+/// ```rust
+/// // Fibbonacci and Lucas numbers.
+/// assert_eq!(quickfib::lucas_uv(1i64, -1, 10u8), (55, 123));
+/// // Mersenne numbers and 2^n + 1.
+/// assert_eq!(quickfib::lucas_uv(3u32, 2, 10u8), (1023, 1025));
+/// ```
+pub fn lucas_uv<T: FibNum>(p: T, q: T, n: impl Into<u64>) -> (T, T) {
+    let pair = lucas_pair(&p, &q, n.into());
+    let v = lucas_v(&p, false, pair.clone());
+    (pair.0, v)
+}
+
+/// Calculate the n-th Pell number, `P(n) = 2P(n-1) + P(n-2)` with `P(0) = 0, P(1) = 1`.
+/// The function may panic if the type T is not large enough to hold the result.
+///
+/// These are the Lucas sequence `U(2, -1)`, computed with the same doubling steps as
+/// [`fibbonacci`](crate::fibbonacci). Negative indices follow `P(-n) = (-1)^(n+1) P(n)`.
+///
+/// # Panics
+/// Panics if `n` is not a whole number.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::pell(10), 2378);
+/// assert_eq!(quickfib::pell(-4), -12);
+/// ```
+pub fn pell<T: FibIndex>(n: T) -> T {
+    let two = T::one().double();
+    let value = lucas_u_index(&n, &two, &T::one());
+    if n.is_negative() && n.is_even() {
+        T::zero() - value
+    } else {
+        value
+    }
+}
+
+/// Calculate the n-th Pell-Lucas number, `Q(n) = 2Q(n-1) + Q(n-2)` with `Q(0) = Q(1) = 2`.
+/// The function may panic if the type T is not large enough to hold the result.
+///
+/// These are the Lucas sequence `V(2, -1)`. Negative indices follow `Q(-n) = (-1)^n Q(n)`.
+///
+/// # Panics
+/// Panics if `n` is not a whole number.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::pell_lucas(10), 6726);
+/// assert_eq!(quickfib::pell_lucas(-3), -14);
+/// ```
+pub fn pell_lucas<T: FibIndex>(n: T) -> T {
+    assert!(n.is_integral(), "non-integral fibbonacci index");
+    let two = T::one().double();
+    let pair = lucas_pair_index(&n, &two, &T::one(), true);
+    let value = lucas_v(&two, true, pair);
+    if n.is_negative() && !n.is_even() {
+        T::zero() - value
+    } else {
+        value
+    }
+}
+
+/// Calculate the n-th Jacobsthal number, `J(n) = J(n-1) + 2J(n-2)` with `J(0) = 0, J(1) = 1`.
+/// The function may panic if the type T is not large enough to hold the result.
+///
+/// These are the Lucas sequence `U(1, -2)`, with the closed form `(2^n - (-1)^n) / 3`.
+///
+/// # Panics
+/// Panics if `n` is negative, where the sequence is not integral, or not a whole number.
+///
+/// # Examples
+/// ```rust
+/// assert_eq!(quickfib::jacobsthal(10), 341);
+/// ```
+pub fn jacobsthal<T: FibIndex>(n: T) -> T {
+    assert!(!n.is_negative(), "negative jacobsthal index");
+    lucas_u_index(&n, &T::one(), &T::one().double())
+}
+
+/// Calculate the Pell numbers for a range of numbers.
+/// The function may panic if the type U is not large enough to hold the result.
+///
+/// # Examples
+/// ```rust
+/// let x = quickfib::pell_range(0..=9);
+/// assert_eq!(x, vec![0, 1, 2, 5, 12, 29, 70, 169, 408, 985]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn pell_range<T, U>(range: T) -> Vec<U>
+where
+    T: IntoIterator<Item = U>,
+    U: FibIndex,
+{
+    let mut result = Vec::new();
+    for i in range {
+        result.push(pell(i));
+    }
+    result
+}
+
+/// Calculate the Pell-Lucas numbers for a range of numbers.
+/// The function may panic if the type U is not large enough to hold the result.
+///
+/// # Examples
+/// ```rust
+/// let x = quickfib::pell_lucas_range(0..=9);
+/// assert_eq!(x, vec![2, 2, 6, 14, 34, 82, 198, 478, 1154, 2786]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn pell_lucas_range<T, U>(range: T) -> Vec<U>
+where
+    T: IntoIterator<Item = U>,
+    U: FibIndex,
+{
+    let mut result = Vec::new();
+    for i in range {
+        result.push(pell_lucas(i));
+    }
+    result
+}
+
+/// Calculate the Jacobsthal numbers for a range of numbers.
+/// The function may panic if the type U is not large enough to hold the result.
+///
+/// # Examples
+/// ```rust
+/// let x = quickfib::jacobsthal_range(0..=9);
+/// assert_eq!(x, vec![0, 1, 1, 3, 5, 11, 21, 43, 85, 171]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn jacobsthal_range<T, U>(range: T) -> Vec<U>
+where
+    T: IntoIterator<Item = U>,
+    U: FibIndex,
+{
+    let mut result = Vec::new();
+    for i in range {
+        result.push(jacobsthal(i));
+    }
+    result
+}
+
+/// The pair `(U(n), U(n+1))` modulo `m`, for residues `p` and `q`. The caller guarantees
+/// `m != 0`.
+fn lucas_pair_mod<M: FibModulus>(p: M, q: M, n: u128, m: M) -> (M, M) {
+    let mut pair = (M::ZERO, M::ONE.reduce(m));
+    for i in (0..u128::BITS - n.leading_zeros()).rev() {
+        let (a, b) = pair;
+        let v = b.add_mod(b, m).sub_mod(p.mul_mod(a, m), m);
+        let c = a.mul_mod(v, m);
+        let d = b.mul_mod(b, m).sub_mod(q.mul_mod(a.mul_mod(a, m), m), m);
+        pair = if (n >> i) & 1 == 1 {
+            (d, p.mul_mod(d, m).sub_mod(q.mul_mod(c, m), m))
+        } else {
+            (c, d)
+        };
+    }
+    pair
+}
+
+/// Calculate the n-th terms `(U(n), V(n))` of the Lucas sequences with parameters `P` and `Q`,
+/// modulo `m`. Intermediate products are widened, so the function never overflows.
+///
+/// `p` and `q` are taken as residues modulo `m`, so a negative `Q` is passed as `m - |Q|`.
+/// This is the building block of the Lucas probable-prime tests.
+///
+/// # Panics
+/// Panics if `m` is zero. See [`try_lucas_uv_mod`] for a fallible alternative.
+///
+/// # Examples
+/// ```rust
+/// let m = 1_000_000_007u64;
+/// // P = 1, Q = -1 gives the fibbonacci and Lucas numbers.
+/// assert_eq!(quickfib::lucas_uv_mod(1, m - 1, 10u8, m), (55, 123));
+/// ```
+pub fn lucas_uv_mod<M: FibModulus>(p: M, q: M, n: impl Into<u128>, m: M) -> (M, M) {
+    assert!(
+        m != M::ZERO,
+        "attempt to calculate the remainder with a divisor of zero"
+    );
+    __lucas_uv_mod(p, q, n.into(), m)
+}
+
+/// Calculate the n-th terms `(U(n), V(n))` of the Lucas sequences with parameters `P` and `Q`,
+/// modulo `m`, returning an error instead of panicking.
+///
+/// # Errors
+/// Returns [`FibError::ModulusZero`] if `m` is zero.
+///
+/// # Examples
+/// ```rust
+/// use quickfib::FibError;
+///
+/// assert_eq!(quickfib::try_lucas_uv_mod(3u8, 2, 5u8, 100), Ok((31, 33)));
+/// assert_eq!(quickfib::try_lucas_uv_mod(3u8, 2, 5u8, 0), Err(FibError::ModulusZero));
+/// ```
+pub fn try_lucas_uv_mod<M: FibModulus>(
+    p: M,
+    q: M,
+    n: impl Into<u128>,
+    m: M,
+) -> Result<(M, M), FibError> {
+    if m == M::ZERO {
+        Err(FibError::ModulusZero)
+    } else {
+        Ok(__lucas_uv_mod(p, q, n.into(), m))
+    }
+}
+
+/// `(U(n), V(n))` modulo `m`. The caller guarantees `m != 0`.
+fn __lucas_uv_mod<M: FibModulus>(p: M, q: M, n: u128, m: M) -> (M, M) {
+    let p = p.reduce(m);
+    let (a, b) = lucas_pair_mod(p, q.reduce(m), n, m);
+    (a, b.add_mod(b, m).sub_mod(p.mul_mod(a, m), m))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{jacobsthal, lucas_uv, lucas_uv_mod, pell, pell_lucas, try_lucas_uv_mod};
+    #[cfg(feature = "alloc")]
+    use super::{jacobsthal_range, pell_lucas_range, pell_range};
+    use crate::{fib_lucas, fibbonacci_mod, FibError};
+    #[cfg(feature = "alloc")]
+    use alloc::vec;
+
+    fn naive(p: i128, q: i128, n: u32) -> (i128, i128) {
+        let (mut u, mut u1) = (0, 1);
+        let (mut v, mut v1) = (2, p);
+        for _ in 0..n {
+            (u, u1) = (u1, p * u1 - q * u);
+            (v, v1) = (v1, p * v1 - q * v);
+        }
+        (u, v)
+    }
+
+    #[test]
+    fn matches_recurrence() {
+        for (p, q) in [(1, -1), (2, -1), (1, -2), (3, 2), (4, 1), (-3, 5), (0, 2)] {
+            for n in 0..=30u32 {
+                let (u, v) = lucas_uv(p, q, n);
+                assert_eq!((u, v), naive(p, q, n), "P = {}, Q = {}, n = {}", p, q, n);
+            }
+        }
+        for n in 0..=89u8 {
+            assert_eq!(lucas_uv(1i64, -1, n), fib_lucas(i64::from(n)));
+        }
+        assert_eq!(lucas_uv(1u64, 0, 40u8), (1, 1));
+    }
+
+    #[test]
+    fn unsigned_limits() {
+        // Mersenne numbers, where 2 U(n+1) is the largest intermediate.
+        assert_eq!(lucas_uv(3u64, 2, 62u8), (u64::MAX >> 2, (1 << 62) + 1));
+        // Chebyshev-like P = 2, Q = 1 gives U(n) = n and V(n) = 2.
+        assert_eq!(lucas_uv(2u64, 1, 1_000_000u32), (1_000_000, 2));
+    }
+
+    #[test]
+    fn modular() {
+        let m = 1_000_000_007u64;
+        for n in [0u128, 1, 2, 10, 1000, u64::MAX as u128, u128::MAX] {
+            let (u, _) = lucas_uv_mod(1, m - 1, n, m);
+            assert_eq!(u, fibbonacci_mod(n, m));
+        }
+        for (p, q) in [(2, -1), (1, -2), (3, 2), (-3, 5)] {
+            for n in 0..=30u32 {
+                let (u, v) = naive(p, q, n);
+                let p = p.rem_euclid(m as i128) as u64;
+                let q = q.rem_euclid(m as i128) as u64;
+                let expected = (
+                    u.rem_euclid(m as i128) as u64,
+                    v.rem_euclid(m as i128) as u64,
+                );
+                assert_eq!(lucas_uv_mod(p, q, n, m), expected);
+            }
+        }
+        // Lucas pseudoprime property: U(p+1) = 0 mod p for p prime with (D/p) = -1.
+        let p = 18446744073709551557u64;
+        let (u, v) = lucas_uv_mod(1, p - 1, u128::from(p) + 1, p);
+        assert_eq!((u, v), (0, p - 2));
+        assert_eq!(try_lucas_uv_mod(3u8, 2, 5u8, 100), Ok((31, 33)));
+        assert_eq!(try_lucas_uv_mod(1u8, 1, 5u8, 0), Err(FibError::ModulusZero));
+    }
+
+    #[test]
+    #[should_panic(expected = "divisor of zero")]
+    fn zero_modulus() {
+        lucas_uv_mod(1u8, 1, 5u8, 0);
+    }
+
+    #[test]
+    fn named_sequences() {
+        for n in 0..=40u32 {
+            let (u, v) = naive(2, -1, n);
+            assert_eq!(pell(i128::from(n)), u);
+            assert_eq!(pell_lucas(i128::from(n)), v);
+            assert_eq!(jacobsthal(i128::from(n)), naive(1, -2, n).0);
+        }
+        assert_eq!(pell(51u64), 11749380235262596085);
+        assert_eq!(pell_lucas(50u64), 13765255184676885126);
+        assert_eq!(jacobsthal(65u64), 12297829382473034411);
+        assert_eq!(pell(101u128), 161733217200188571081311986634082331709);
+        assert_eq!(pell_lucas(100u128), 189482250299273866835746159841800035874);
+        assert_eq!(jacobsthal(129u128), 226854911280625642308916404954512140971);
+        assert_eq!(pell(10.0f64), 2378.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "negative jacobsthal index")]
+    fn negative_jacobsthal() {
+        jacobsthal(-1);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn named_ranges() {
+        assert_eq!(pell_range(-4..=4), vec![-12, 5, -2, 1, 0, 1, 2, 5, 12]);
+        assert_eq!(
+            pell_lucas_range(-4..=4),
+            vec![34, -14, 6, -2, 2, 2, 6, 14, 34]
+        );
+        assert_eq!(jacobsthal_range(0u16..=11).last(), Some(&683));
+    }
+}
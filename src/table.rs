@@ -0,0 +1,190 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::factor::lcm;
+use crate::modular::fib_pair_mod;
+use crate::pisano::prime_entry_point_with;
+use crate::{FibModContext, FibModulus};
+
+/// The pisano period π(m) and the entry point z(m) of every modulus `1 <= m <= n`.
+///
+/// Built with a smallest-prime-factor sieve: every prime gets its entry point from the
+/// divisors of `p - 1` or `p + 1`, prime powers are lifted from the prime below them,
+/// and every other modulus combines its coprime parts with a least common multiple.
+/// The table stores z(m) and the ratio π(m) / z(m), which is always 1, 2 or 4.
+///
+/// # Examples
+/// ```rust
+/// use quickfib::PisanoTable;
+///
+/// let table = PisanoTable::new(100);
+/// assert_eq!(table.pisano_period(10), Some(60));
+/// assert_eq!(table.entry_point(10), Some(15));
+/// assert_eq!(table.pisano_period(101), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PisanoTable {
+    entry: Box<[u64]>,
+    ratio: Box<[u8]>,
+}
+
+impl PisanoTable {
+    /// Compute the table for every modulus up to and including `n`.
+    pub fn new(n: u32) -> Self {
+        let n = n as usize;
+        let spf = smallest_prime_factors(n + 1);
+        let mut entry = vec![0u64; n + 1];
+        let mut ratio = vec![0u8; n + 1];
+        if n >= 1 {
+            entry[1] = 1;
+            ratio[1] = 1;
+        }
+
+        for m in 2..=n {
+            let p = spf[m] as usize;
+            let mut pk = p;
+            while (m / pk).is_multiple_of(p) {
+                pk *= p;
+            }
+            let rest = m / pk;
+
+            let z = if rest > 1 {
+                lcm(entry[pk], entry[rest])
+            } else if pk == p {
+                prime_entry_point(p as u32, &spf)
+            } else {
+                // z(p^k) is z(p^(k-1)) or p times it.
+                let z = entry[m / p];
+                if fib_pair_mod(&z, m as u32).0 == 0 {
+                    z
+                } else {
+                    z * p as u64
+                }
+            };
+            entry[m] = z;
+            ratio[m] = if rest > 1 {
+                let period = lcm(
+                    entry[pk] * u64::from(ratio[pk]),
+                    entry[rest] * u64::from(ratio[rest]),
+                );
+                (period / z) as u8
+            } else {
+                period_ratio(z, m as u32)
+            };
+        }
+
+        PisanoTable {
+            entry: entry.into_boxed_slice(),
+            ratio: ratio.into_boxed_slice(),
+        }
+    }
+
+    /// The largest modulus covered by the table.
+    pub fn limit(&self) -> u32 {
+        (self.entry.len() - 1) as u32
+    }
+
+    /// The pisano period π(m), or `None` if `m` is zero or beyond the table.
+    pub fn pisano_period(&self, m: u32) -> Option<u64> {
+        self.entry_point(m)
+            .map(|z| z * u64::from(self.ratio[m as usize]))
+    }
+
+    /// The entry point z(m), the smallest `n > 0` with `m | F(n)`, or `None` if `m` is zero
+    /// or beyond the table.
+    pub fn entry_point(&self, m: u32) -> Option<u64> {
+        match m {
+            0 => None,
+            _ => self.entry.get(m as usize).copied(),
+        }
+    }
+}
+
+/// The smallest prime factor of every number up to and including `n`.
+fn smallest_prime_factors(n: usize) -> Vec<u32> {
+    let mut spf = vec![0u32; n + 1];
+    for i in 2..=n {
+        if spf[i] == 0 {
+            spf[i] = i as u32;
+            let Some(start) = i.checked_mul(i) else {
+                continue;
+            };
+            for j in (start..=n).step_by(i) {
+                if spf[j] == 0 {
+                    spf[j] = i as u32;
+                }
+            }
+        }
+    }
+    spf
+}
+
+/// The distinct prime factors of `x`, read off the sieve.
+fn sieve_primes(mut x: u64, spf: &[u32]) -> impl Iterator<Item = u64> + '_ {
+    core::iter::from_fn(move || {
+        (x > 1).then(|| {
+            let q = u64::from(spf[x as usize]);
+            while x.is_multiple_of(q) {
+                x /= q;
+            }
+            q
+        })
+    })
+}
+
+/// The entry point of the prime `p`, with the prime factors read off the sieve.
+fn prime_entry_point(p: u32, spf: &[u32]) -> u64 {
+    let ctx = FibModContext::new(p.into()).expect("primes are nonzero");
+    prime_entry_point_with(p.into(), |d| sieve_primes(d, spf), |z| ctx.fib(z) == 0) as u64
+}
+
+/// The ratio `π(m) / z(m)` given the entry point `z = z(m)`: the multiplicative order of
+/// `F(z + 1)` modulo `m`, which is always 1, 2 or 4.
+fn period_ratio(z: u64, m: u32) -> u8 {
+    let x = fib_pair_mod(&z, m).1;
+    if x == 1 % m {
+        1
+    } else if x.mul_mod(x, m) == 1 % m {
+        2
+    } else {
+        4
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::PisanoTable;
+    use crate::pisano_period;
+
+    #[test]
+    fn matches_pisano_period() {
+        let table = PisanoTable::new(3000);
+        assert_eq!(table.limit(), 3000);
+        assert_eq!(table.pisano_period(0), None);
+        assert_eq!(table.pisano_period(3001), None);
+        for m in 1..=3000u32 {
+            assert_eq!(
+                u128::from(table.pisano_period(m).unwrap()),
+                pisano_period(m.into()),
+                "m = {}",
+                m
+            );
+        }
+    }
+
+    #[test]
+    fn entry_points() {
+        let table = PisanoTable::new(500);
+        for m in 1..=500u32 {
+            let (mut a, mut b, mut n) = (1u32 % m, 1u32 % m, 1);
+            while a != 0 {
+                (a, b) = (b, (a + b) % m);
+                n += 1;
+            }
+            assert_eq!(table.entry_point(m), Some(n), "m = {}", m);
+        }
+        assert_eq!(PisanoTable::new(0).limit(), 0);
+    }
+}
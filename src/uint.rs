@@ -0,0 +1,424 @@
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Add, Mul, Sub};
+
+use crate::{BitIndex, FibIndex, FibNum, FibPrimitive};
+
+/// A fixed-width unsigned integer made of `LIMBS` 64-bit limbs, stored on the stack.
+///
+/// The operators panic on overflow; use the `checked_`, `wrapping_` and `overflowing_`
+/// methods for other behaviours. It implements [`FibPrimitive`], so all the fibbonacci
+/// variants work with it without a heap.
+///
+/// # Examples
+/// ```rust
+/// use quickfib::U256;
+///
+/// let x = quickfib::fib::<U256>(370u32);
+/// assert_eq!(
+///     x.to_string(),
+///     "94611056096305838013295371573764256526437182762229865607320618320601813254535"
+/// );
+/// assert_eq!(quickfib::checked_fibbonacci(U256::from(371u32)), None);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Uint<const LIMBS: usize> {
+    limbs: [u64; LIMBS],
+}
+
+/// A 256-bit unsigned integer, holding fibbonacci numbers up to `F(370)`.
+pub type U256 = Uint<4>;
+/// A 512-bit unsigned integer, holding fibbonacci numbers up to `F(739)`.
+pub type U512 = Uint<8>;
+/// A 1024-bit unsigned integer, holding fibbonacci numbers up to `F(1476)`.
+pub type U1024 = Uint<16>;
+
+impl<const LIMBS: usize> Uint<LIMBS> {
+    /// The value `0`.
+    pub const ZERO: Self = Uint { limbs: [0; LIMBS] };
+    /// The value `1`.
+    pub const ONE: Self = Self::from_u64(1);
+    /// The largest value, `2^(64 * LIMBS) - 1`.
+    pub const MAX: Self = Uint {
+        limbs: [u64::MAX; LIMBS],
+    };
+    /// The number of bits of the type.
+    pub const BITS: u32 = 64 * LIMBS as u32;
+
+    /// Create an integer from little-endian limbs.
+    pub const fn from_limbs(limbs: [u64; LIMBS]) -> Self {
+        Uint { limbs }
+    }
+
+    /// The little-endian limbs of the integer.
+    pub const fn to_limbs(self) -> [u64; LIMBS] {
+        self.limbs
+    }
+
+    const fn from_u64(value: u64) -> Self {
+        let mut limbs = [0; LIMBS];
+        limbs[0] = value;
+        Uint { limbs }
+    }
+
+    /// Addition, returning the wrapped sum and whether an overflow occurred.
+    pub fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+        let mut out = [0; LIMBS];
+        let mut carry = false;
+        for (i, limb) in out.iter_mut().enumerate() {
+            let (s, c1) = self.limbs[i].overflowing_add(rhs.limbs[i]);
+            let (s, c2) = s.overflowing_add(u64::from(carry));
+            *limb = s;
+            carry = c1 || c2;
+        }
+        (Uint { limbs: out }, carry)
+    }
+
+    /// Subtraction, returning the wrapped difference and whether an overflow occurred.
+    pub fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+        let mut out = [0; LIMBS];
+        let mut borrow = false;
+        for (i, limb) in out.iter_mut().enumerate() {
+            let (d, b1) = self.limbs[i].overflowing_sub(rhs.limbs[i]);
+            let (d, b2) = d.overflowing_sub(u64::from(borrow));
+            *limb = d;
+            borrow = b1 || b2;
+        }
+        (Uint { limbs: out }, borrow)
+    }
+
+    /// Multiplication, returning the wrapped product and whether an overflow occurred.
+    pub fn overflowing_mul(self, rhs: Self) -> (Self, bool) {
+        let mut out = [0; LIMBS];
+        let mut overflow = false;
+        for (i, &x) in self.limbs.iter().enumerate() {
+            if x == 0 {
+                continue;
+            }
+            let mut carry = 0u128;
+            for (j, &y) in rhs.limbs.iter().enumerate() {
+                if i + j >= LIMBS {
+                    overflow |= y != 0;
+                    continue;
+                }
+                let t = u128::from(x) * u128::from(y) + u128::from(out[i + j]) + carry;
+                out[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            overflow |= carry != 0;
+        }
+        (Uint { limbs: out }, overflow)
+    }
+
+    /// Checked addition, returning `None` on overflow.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_add(rhs) {
+            (sum, false) => Some(sum),
+            (_, true) => None,
+        }
+    }
+
+    /// Checked subtraction, returning `None` on overflow.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_sub(rhs) {
+            (diff, false) => Some(diff),
+            (_, true) => None,
+        }
+    }
+
+    /// Checked multiplication, returning `None` on overflow.
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_mul(rhs) {
+            (product, false) => Some(product),
+            (_, true) => None,
+        }
+    }
+
+    /// Wrapping (modular) addition.
+    pub fn wrapping_add(self, rhs: Self) -> Self {
+        self.overflowing_add(rhs).0
+    }
+
+    /// Wrapping (modular) subtraction.
+    pub fn wrapping_sub(self, rhs: Self) -> Self {
+        self.overflowing_sub(rhs).0
+    }
+
+    /// Wrapping (modular) multiplication.
+    pub fn wrapping_mul(self, rhs: Self) -> Self {
+        self.overflowing_mul(rhs).0
+    }
+
+    /// Divide in place by `d`, returning the remainder.
+    fn div_rem_u64(&mut self, d: u64) -> u64 {
+        let mut rem = 0u128;
+        for limb in self.limbs.iter_mut().rev() {
+            let cur = (rem << 64) | u128::from(*limb);
+            *limb = (cur / u128::from(d)) as u64;
+            rem = cur % u128::from(d);
+        }
+        rem as u64
+    }
+}
+
+impl<const LIMBS: usize> Default for Uint<LIMBS> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+macro_rules! impl_from_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl<const LIMBS: usize> From<$t> for Uint<LIMBS> {
+                fn from(value: $t) -> Self {
+                    Self::from_u64(u64::from(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_primitive!(u8, u16, u32, u64);
+
+impl<const LIMBS: usize> Ord for Uint<LIMBS> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs.iter().rev().cmp(other.limbs.iter().rev())
+    }
+}
+
+impl<const LIMBS: usize> PartialOrd for Uint<LIMBS> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+macro_rules! impl_op {
+    ($($imp:ident, $method:ident, $checked:ident, $msg:literal);*) => {
+        $(
+            impl<const LIMBS: usize> $imp for Uint<LIMBS> {
+                type Output = Self;
+
+                fn $method(self, rhs: Self) -> Self {
+                    self.$checked(rhs).expect($msg)
+                }
+            }
+        )*
+    };
+}
+
+impl_op! {
+    Add, add, checked_add, "attempt to add with overflow";
+    Sub, sub, checked_sub, "attempt to subtract with overflow";
+    Mul, mul, checked_mul, "attempt to multiply with overflow"
+}
+
+impl<const LIMBS: usize> FibNum for Uint<LIMBS> {
+    fn zero() -> Self {
+        Self::ZERO
+    }
+
+    fn one() -> Self {
+        Self::ONE
+    }
+}
+
+impl<const LIMBS: usize> FibIndex for Uint<LIMBS> {
+    fn is_even(&self) -> bool {
+        self.limbs[0] & 1 == 0
+    }
+
+    fn halve(&self) -> Self {
+        let mut limbs = self.limbs;
+        let mut carry = 0;
+        for limb in limbs.iter_mut().rev() {
+            let next = *limb << 63;
+            *limb = (*limb >> 1) | carry;
+            carry = next;
+        }
+        Uint { limbs }
+    }
+}
+
+impl<const LIMBS: usize> BitIndex for Uint<LIMBS> {
+    fn bit_len(&self) -> u64 {
+        match self.limbs.iter().rposition(|&l| l != 0) {
+            Some(i) => (i as u64 + 1) * 64 - u64::from(self.limbs[i].leading_zeros()),
+            None => 0,
+        }
+    }
+
+    fn bit(&self, i: u64) -> bool {
+        let limb = (i / 64) as usize;
+        limb < LIMBS && (self.limbs[limb] >> (i % 64)) & 1 == 1
+    }
+}
+
+impl<const LIMBS: usize> FibPrimitive for Uint<LIMBS> {
+    const MIN: Self = Self::ZERO;
+    const MAX: Self = Self::MAX;
+    // `F(n)` has about `n * log2(phi) - log2(sqrt(5))` bits.
+    const MAX_INDEX: Self =
+        Self::from_u64((Self::BITS as u64 * 1_000_000_000 + 1_160_964_047) / 694_241_913);
+    // `L(n)` has about `n * log2(phi)` bits.
+    const MAX_LUCAS_INDEX: Self = Self::from_u64(Self::BITS as u64 * 1_000_000_000 / 694_241_913);
+
+    fn wrapping_add(self, rhs: Self) -> Self {
+        Uint::wrapping_add(self, rhs)
+    }
+
+    fn wrapping_sub(self, rhs: Self) -> Self {
+        Uint::wrapping_sub(self, rhs)
+    }
+
+    fn wrapping_mul(self, rhs: Self) -> Self {
+        Uint::wrapping_mul(self, rhs)
+    }
+
+    fn checked_add(self, rhs: Self) -> Option<Self> {
+        Uint::checked_add(self, rhs)
+    }
+
+    fn checked_mul(self, rhs: Self) -> Option<Self> {
+        Uint::checked_mul(self, rhs)
+    }
+}
+
+impl<const LIMBS: usize> fmt::Display for Uint<LIMBS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 20 decimal digits are enough for every 64-bit limb.
+        let mut buf = [[b'0'; 20]; LIMBS];
+        let buf = buf.as_flattened_mut();
+        let mut x = *self;
+        let mut start = buf.len();
+        loop {
+            let mut chunk = x.div_rem_u64(10_000_000_000_000_000_000);
+            let last = x == Self::ZERO;
+            for _ in 0..19 {
+                start -= 1;
+                buf[start] = b'0' + (chunk % 10) as u8;
+                chunk /= 10;
+                if last && chunk == 0 {
+                    break;
+                }
+            }
+            if last {
+                break;
+            }
+        }
+        f.pad_integral(true, "", core::str::from_utf8(&buf[start..]).unwrap())
+    }
+}
+
+impl<const LIMBS: usize> fmt::Debug for Uint<LIMBS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+macro_rules! impl_hex {
+    ($($imp:ident => $digits:literal),*) => {
+        $(
+            impl<const LIMBS: usize> fmt::$imp for Uint<LIMBS> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    let mut buf = [[b'0'; 16]; LIMBS];
+                    let buf = buf.as_flattened_mut();
+                    for (i, limb) in self.limbs.iter().enumerate() {
+                        for k in 0..16 {
+                            let nibble = (limb >> (4 * k)) & 0xf;
+                            buf[buf.len() - 1 - (16 * i + k)] = $digits[nibble as usize];
+                        }
+                    }
+                    let start = buf.iter().position(|&d| d != b'0').unwrap_or(buf.len() - 1);
+                    f.pad_integral(true, "0x", core::str::from_utf8(&buf[start..]).unwrap())
+                }
+            }
+        )*
+    };
+}
+
+impl_hex!(LowerHex => b"0123456789abcdef", UpperHex => b"0123456789ABCDEF");
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Uint, U1024, U256, U512};
+    use crate::{checked_fibbonacci, fib, FibPrimitive};
+    use core::fmt::Write;
+
+    struct Buf {
+        bytes: [u8; 128],
+        len: usize,
+    }
+
+    impl Write for Buf {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            self.bytes[self.len..self.len + s.len()].copy_from_slice(s.as_bytes());
+            self.len += s.len();
+            Ok(())
+        }
+    }
+
+    fn check_fmt(args: core::fmt::Arguments<'_>, expected: core::fmt::Arguments<'_>) {
+        let mut a = Buf {
+            bytes: [0; 128],
+            len: 0,
+        };
+        let mut b = Buf {
+            bytes: [0; 128],
+            len: 0,
+        };
+        a.write_fmt(args).unwrap();
+        b.write_fmt(expected).unwrap();
+        assert_eq!(&a.bytes[..a.len], &b.bytes[..b.len]);
+    }
+
+    #[test]
+    fn max_index() {
+        assert_eq!(U256::MAX_INDEX, U256::from(370u32));
+        assert_eq!(U512::MAX_INDEX, U512::from(739u32));
+        assert_eq!(U1024::MAX_INDEX, U1024::from(1476u32));
+        assert_eq!(<Uint<2> as FibPrimitive>::MAX_INDEX, Uint::from(186u32));
+        assert_eq!(U256::MAX_LUCAS_INDEX, U256::from(368u32));
+        assert_eq!(U1024::MAX_LUCAS_INDEX, U1024::from(1474u32));
+        assert_eq!(
+            <Uint<2> as FibPrimitive>::MAX_LUCAS_INDEX,
+            Uint::from(184u32)
+        );
+    }
+
+    #[test]
+    fn calc_matches_u128() {
+        for n in 0..=186u32 {
+            let x = fib::<Uint<2>>(n).to_limbs();
+            let expected = fib::<u128>(n);
+            assert_eq!(x, [expected as u64, (expected >> 64) as u64]);
+        }
+        assert!(checked_fibbonacci(U1024::from(1476u32)).is_some());
+        assert_eq!(checked_fibbonacci(U1024::from(1477u32)), None);
+    }
+
+    #[test]
+    fn formatting() {
+        for x in [0, 7, 10_000_000_000_000_000_000, u128::MAX / 3, u128::MAX] {
+            let y = Uint::<2>::from_limbs([x as u64, (x >> 64) as u64]);
+            check_fmt(format_args!("{}", y), format_args!("{}", x));
+            check_fmt(format_args!("{:>45}", y), format_args!("{:>45}", x));
+            check_fmt(format_args!("{:x}", y), format_args!("{:x}", x));
+            check_fmt(format_args!("{:#X}", y), format_args!("{:#X}", x));
+        }
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        assert_eq!(U256::MAX.checked_add(U256::ONE), None);
+        assert_eq!(U256::ZERO.checked_sub(U256::ONE), None);
+        assert_eq!(U256::MAX.wrapping_add(U256::ONE), U256::ZERO);
+        let half = U256::from_limbs([0, 0, 1, 0]);
+        assert_eq!(half.checked_mul(half), None);
+        assert_eq!(
+            U256::from(u64::MAX).checked_mul(U256::from(u64::MAX)),
+            Some(U256::from_limbs([1, u64::MAX - 1, 0, 0]))
+        );
+    }
+}